@@ -2,3 +2,43 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Fixed-point scale of the cumulative borrow indices (1.0 == INDEX_SCALE).
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Pyth oracle program; price accounts owned by any other program are rejected.
+pub const PYTH_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi9epH");
+
+/// USD values derived from oracle prices are fixed-point numbers with this many decimals.
+pub const VALUE_DECIMALS: u32 = 6;
+
+// Oracle guard defaults applied to new pools; the authority can change them with `update_pool_oracles`.
+pub const DEFAULT_MAX_PRICE_AGE_SLOTS: u64 = 150;
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: u32 = 60;
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u16 = 200;
+pub const DEFAULT_MAX_EMA_DEVIATION_BPS: u16 = 500;
+
+/// Share of a position's debt, in percent, a single liquidation may repay on new pools.
+pub const DEFAULT_CLOSE_FACTOR: u8 = 50;
+
+/// Fee charged on flash loans from new pools, in basis points of the amount borrowed.
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: u16 = 9;
+
+/// Share of accrued interest, in basis points, new pools keep as protocol reserves.
+pub const DEFAULT_RESERVE_FACTOR_BPS: u16 = 1_000;
+
+/// Fee charged when a loan is opened on new pools, in basis points of the principal.
+pub const DEFAULT_ORIGINATION_FEE_BPS: u16 = 50;
+
+/// Fee charged when a loan is extended on new pools, in basis points of the principal.
+pub const DEFAULT_ROLLOVER_FEE_BPS: u16 = 0;
+
+/// Seconds an expired loan on new pools can still be repaid before it becomes liquidatable.
+pub const DEFAULT_GRACE_PERIOD_SECONDS: u32 = 24 * 60 * 60;
+
+/// Minimum seconds of interest a loan on new pools is charged when repaid early.
+pub const DEFAULT_MIN_INTEREST_PERIOD_SECONDS: u32 = 24 * 60 * 60;
@@ -36,5 +36,79 @@ pub enum ErrorCode {
 
     LoanNotExpired,
 
-    InvalidBorrower
+    InvalidBorrower,
+
+    #[msg("Position has no outstanding debt to liquidate")]
+    NothingToLiquidate,
+
+    #[msg("Position is healthy and the loan has not expired")]
+    PositionNotLiquidatable,
+
+    #[msg("Liquidity provider account belongs to a different pool")]
+    InvalidLiquidityPool,
+
+    #[msg("Not enough unborrowed liquidity in the pool")]
+    InsufficientLiquidity,
+
+    #[msg("Loan id already has an outstanding loan")]
+    LoanAlreadyActive,
+
+    #[msg("Oracle account is not a valid price account for this pool")]
+    InvalidOracle,
+
+    #[msg("Oracle price is unavailable or not positive")]
+    InvalidOraclePrice,
+
+    #[msg("Oracle price is older than the pool allows")]
+    StaleOraclePrice,
+
+    #[msg("Oracle confidence interval is too wide relative to the price")]
+    OracleConfidenceTooWide,
+
+    #[msg("Oracle spot price deviates too far from its EMA price")]
+    OraclePriceDeviationTooLarge,
+
+    InvalidCloseFactor,
+
+    #[msg("Fee must not exceed 10000 basis points")]
+    InvalidFee,
+
+    #[msg("A flash loan is outstanding on this pool")]
+    FlashLoanActive,
+
+    #[msg("No matching flash loan is outstanding on this pool")]
+    NoFlashLoanActive,
+
+    #[msg("Flash borrow must be followed by a matching flash repay in the same transaction")]
+    FlashRepayNotFound,
+
+    #[msg("Flash loans cannot be taken through a cross-program invocation")]
+    FlashLoanCpiNotAllowed,
+
+    #[msg("Treasury account does not match the pool's configured treasury")]
+    InvalidTreasury,
+
+    #[msg("Withdrawal exceeds the pool's per-call protocol fee limit")]
+    FeeWithdrawalLimitExceeded,
+
+    #[msg("Not enough protocol fees in the fee vault")]
+    InsufficientFees,
+
+    #[msg("Loan is past its grace period and can no longer be extended")]
+    LoanPastGracePeriod,
+
+    #[msg("Loan has no outstanding debt")]
+    LoanNotActive,
+
+    #[msg("Collateral amount must be greater than zero")]
+    InvalidCollateralAmount,
+
+    #[msg("Withdrawal would leave the loan above the pool's loan-to-value ratio")]
+    WithdrawalExceedsLtv,
+
+    #[msg("Borrow amount exceeds the position's available borrow capacity")]
+    BorrowCapacityExceeded,
+
+    #[msg("Loan term has ended")]
+    LoanExpired
 }
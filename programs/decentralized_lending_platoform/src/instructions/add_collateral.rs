@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::{BorrowInfo, LiquidityPool};
+use crate::error::ErrorCode;
+use crate::oracle::is_unusable_price;
+
+#[derive(Accounts)]
+pub struct AddCollateral<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(address = borrower_account_info.collateral_mint @ ErrorCode::InvalidMint)]
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
+        bump,
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_collateral_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_add_collateral(ctx: Context<AddCollateral>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrow_info = &mut ctx.accounts.borrower_account_info;
+    let collateral_mint = &ctx.accounts.collateral_mint;
+
+    require!(amount > 0, ErrorCode::InvalidCollateralAmount);
+    require!(
+        !borrow_info.is_closed && borrow_info.total_debt()? > 0,
+        ErrorCode::LoanNotActive
+    );
+
+    liquidity_pool.accrue_interest(clock.unix_timestamp)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrow_info.borrow_mint);
+    borrow_info.accrue_interest(borrow_index)?;
+
+    let collateral_vault = if borrow_info.collateral_mint == liquidity_pool.mint_a {
+        &ctx.accounts.collateral_vault_a
+    } else {
+        &ctx.accounts.collateral_vault_b
+    };
+
+    let cpi_accounts = TransferChecked {
+        from: ctx.accounts.borrower_collateral_ata.to_account_info(),
+        to: collateral_vault.to_account_info(),
+        authority: ctx.accounts.borrower.to_account_info(),
+        mint: collateral_mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_ctx, amount, collateral_mint.decimals)?;
+
+    borrow_info.total_collateral = borrow_info
+        .total_collateral
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // A position brought back above the threshold is no longer up for liquidation. Adding collateral only improves
+    // health, so the deposit goes through even when prices cannot be loaded, e.g. during an oracle outage
+    let prices = match liquidity_pool.loan_prices(&borrow_info.borrow_mint, &ctx.accounts.oracle_a, &ctx.accounts.oracle_b, &clock) {
+        Ok(prices) => Some(prices),
+        Err(err) if is_unusable_price(&err) => {
+            msg!("Skipping the health check, price rejected: {}", err);
+            None
+        }
+        Err(err) => return Err(err),
+    };
+    if let Some((borrow_price, collateral_price)) = prices {
+        let is_unhealthy = borrow_info.is_unhealthy(liquidity_pool.liquidation_threshold, &borrow_price, &collateral_price)?;
+        borrow_info.update_unhealthy_since(is_unhealthy, clock.unix_timestamp);
+    }
+
+    msg!("Added {} collateral to loan {}, now {}", amount, borrow_info.loan_id, borrow_info.total_collateral);
+
+    Ok(())
+}
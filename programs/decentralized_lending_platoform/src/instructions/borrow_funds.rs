@@ -6,6 +6,7 @@ use crate::{BorrowDuration, BorrowInfo, LiquidityPool};
 use crate::error::ErrorCode;
 
 #[derive(Accounts)]
+#[instruction(loan_id: u64)]
 pub struct BorrowFunds<'info> {
     #[account(mut)]
     pub borrower: Signer<'info>,
@@ -13,15 +14,6 @@ pub struct BorrowFunds<'info> {
     pub wanted_mint: Account<'info, Mint>,
     pub giving_mint: Account<'info, Mint>,
 
-    #[account(
-        init_if_needed,
-        payer = borrower,
-        space = 8 + BorrowInfo::INIT_SPACE,
-        seeds = [b"borrower_account", borrower.key().as_ref()],
-        bump
-    )]
-    pub borrower_account_info: Account<'info, BorrowInfo>,
-
     #[account(
         mut,
         seeds = [
@@ -34,6 +26,15 @@ pub struct BorrowFunds<'info> {
     )]
     pub liquidity_pool: Account<'info, LiquidityPool>,
 
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        space = 8 + BorrowInfo::INIT_SPACE,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &loan_id.to_le_bytes()],
+        bump
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
     #[account(
         mut,
         seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
@@ -52,6 +53,24 @@ pub struct BorrowFunds<'info> {
     )]
     pub token_vault_b: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_b: Account<'info, TokenAccount>,
+
     #[account(
         mut,
         seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
@@ -84,6 +103,14 @@ pub struct BorrowFunds<'info> {
     )]
     pub borrower_collateral_ata: Account<'info, TokenAccount>,
 
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -91,8 +118,9 @@ pub struct BorrowFunds<'info> {
 }
 
 
-pub fn borrow_funds(
+pub fn process_borrow_funds(
     ctx: Context<BorrowFunds>,
+    loan_id: u64,
     amount: u64,
     borrow_duration: u8,
 ) -> Result<()> {
@@ -110,12 +138,40 @@ pub fn borrow_funds(
         return Err(ErrorCode::InvalidMint.into());
     };
 
+    require!(amount > 0, ErrorCode::InvalidCollateralAmount);
+
+    // Each loan id holds a single independent loan; a repaid or liquidated id can be reused
+    require!(borrower_account_info.total_debt()? == 0, ErrorCode::LoanAlreadyActive);
+
+    let borrow_duration_value = BorrowDuration::from_u8(borrow_duration)?;
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    liquidity_pool.accrue_interest(now)?;
+
+    let (borrow_price, collateral_price) = liquidity_pool.loan_prices(
+        &borrow_mint,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        &clock,
+    )?;
+
+    // Lend up to `ltv` percent of the collateral's USD value, paid out in the borrowed mint
     let ltv = liquidity_pool.ltv_ratio; // e.g. 50 means 50%
-    let borrow_amount = amount
-        .checked_mul(ltv as u64)
+    let borrow_value = collateral_price
+        .value_of(amount)?
+        .checked_mul(ltv as u128)
         .ok_or(ErrorCode::MathOverflow)?
         .checked_div(100)
         .ok_or(ErrorCode::MathOverflow)?;
+    let borrow_amount = borrow_price.amount_for(borrow_value)?;
+    require!(borrow_amount > 0, ErrorCode::InvalidLiquidityAmount);
+
+    // The borrower owes the full principal but receives it net of the origination fee
+    let origination_fee = liquidity_pool.fee_config.origination_fee(borrow_amount);
+    let disbursed_amount = borrow_amount - origination_fee;
+
+    liquidity_pool.sub_available(&borrow_mint, borrow_amount)?;
 
     transfer_checked(
         CpiContext::new(
@@ -123,9 +179,9 @@ pub fn borrow_funds(
             TransferChecked {
                 from: ctx.accounts.borrower_collateral_ata.to_account_info(),
                 to: if collateral_mint == liquidity_pool.mint_a {
-                    ctx.accounts.token_vault_a.to_account_info()
+                    ctx.accounts.collateral_vault_a.to_account_info()
                 } else {
-                    ctx.accounts.token_vault_b.to_account_info()
+                    ctx.accounts.collateral_vault_b.to_account_info()
                 },
                 authority: borrower.to_account_info(),
                 mint: giving_mint.to_account_info(),
@@ -135,10 +191,10 @@ pub fn borrow_funds(
         giving_mint.decimals,
     )?;
 
-    let vault_account = if borrow_mint == liquidity_pool.mint_a {
-        ctx.accounts.token_vault_a.to_account_info()
+    let (vault_account, fee_vault_account) = if borrow_mint == liquidity_pool.mint_a {
+        (ctx.accounts.token_vault_a.to_account_info(), ctx.accounts.fee_vault_a.to_account_info())
     } else {
-        ctx.accounts.token_vault_b.to_account_info()
+        (ctx.accounts.token_vault_b.to_account_info(), ctx.accounts.fee_vault_b.to_account_info())
     };
 
     let seeds = &[
@@ -160,44 +216,54 @@ pub fn borrow_funds(
             },
             &[seeds],
         ),
-        borrow_amount,
+        disbursed_amount,
         wanted_mint.decimals,
     )?;
 
+    if origination_fee > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_account.clone(),
+                    to: fee_vault_account,
+                    authority: liquidity_pool.to_account_info(),
+                    mint: wanted_mint.to_account_info(),
+                },
+                &[seeds],
+            ),
+            origination_fee,
+            wanted_mint.decimals,
+        )?;
+    }
+
     borrower_account_info.borrower = borrower.key();
     borrower_account_info.borrowed_from_pool = liquidity_pool.key();
-    borrower_account_info.total_borrowed = borrower_account_info.total_borrowed.checked_add(borrow_amount).unwrap();
-    borrower_account_info.total_collateral = borrower_account_info.total_collateral.checked_add(amount).unwrap();
-    borrower_account_info.borrowed_at = Clock::get()?.unix_timestamp;
+    borrower_account_info.loan_id = loan_id;
+    borrower_account_info.borrow_mint = borrow_mint;
+    borrower_account_info.collateral_mint = collateral_mint;
+    borrower_account_info.total_borrowed = borrow_amount;
+    borrower_account_info.origination_fee = origination_fee;
+    borrower_account_info.accrued_interest = 0;
+    borrower_account_info.borrow_index = liquidity_pool.borrow_index(&borrow_mint);
+    borrower_account_info.total_collateral = amount;
+    borrower_account_info.unhealthy_since = 0;
     borrower_account_info.repaid_amount = 0;
     borrower_account_info.is_closed = false;
 
-    let borrow_duration_value = match borrow_duration {
-        0 => BorrowDuration::TenDays,
-        1 => BorrowDuration::TwentyDays,
-        2 => BorrowDuration::TwentyDays,
-        _ => BorrowDuration::TenDays
-    };
+    borrower_account_info.start_term(now, liquidity_pool.borrow_index(&borrow_mint));
 
     borrower_account_info.borrow_duration = borrow_duration_value;
-
-    liquidity_pool.total_liquidity = liquidity_pool.total_liquidity.checked_sub(borrow_amount).unwrap();
-
-    if borrow_mint == liquidity_pool.mint_a {
-        liquidity_pool.total_borrowed_a = liquidity_pool.total_borrowed_a
-            .checked_add(borrow_amount)
-            .ok_or(ErrorCode::MathOverflow)?;
-    } else if borrow_mint == liquidity_pool.mint_b {
-        liquidity_pool.total_borrowed_b = liquidity_pool.total_borrowed_b
-            .checked_add(borrow_amount)
-            .ok_or(ErrorCode::MathOverflow)?;
-    };
-
-    liquidity_pool.total_borrowed = liquidity_pool
-        .total_borrowed_a
-        .checked_add(liquidity_pool.total_borrowed_b)
+    borrower_account_info.expires_at = now
+        .checked_add(borrow_duration_value.seconds())
         .ok_or(ErrorCode::MathOverflow)?;
 
+    liquidity_pool.add_borrowed(&borrow_mint, borrow_amount)?;
+    msg!(
+        "Pool utilization is now {} bps with {} left available",
+        liquidity_pool.utilization(&borrow_mint),
+        liquidity_pool.available(&borrow_mint)
+    );
 
     msg!("Borrower Account Info: {:?}", borrower_account_info);
     msg!("Pool Info: {:?}", liquidity_pool);
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::{BorrowInfo, LiquidityPool};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct BorrowMore<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(address = borrower_account_info.borrow_mint @ ErrorCode::InvalidMint)]
+    pub loan_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
+        bump,
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_b: Account<'info, TokenAccount>,
+
+    // Borrower's token account to receive the additional funds
+    #[account(
+        mut,
+        associated_token::mint = loan_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_borrow_more(ctx: Context<BorrowMore>, borrow_amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrow_info = &mut ctx.accounts.borrower_account_info;
+    let loan_mint = &ctx.accounts.loan_mint;
+
+    require!(borrow_amount > 0, ErrorCode::InvalidLiquidityAmount);
+    require!(
+        !borrow_info.is_closed && borrow_info.total_debt()? > 0,
+        ErrorCode::LoanNotActive
+    );
+    require!(clock.unix_timestamp <= borrow_info.expires_at, ErrorCode::LoanExpired);
+
+    liquidity_pool.accrue_interest(clock.unix_timestamp)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrow_info.borrow_mint);
+    borrow_info.accrue_interest(borrow_index)?;
+
+    // Draw against the collateral already posted, up to the pool's loan-to-value ratio
+    let (borrow_price, collateral_price) = liquidity_pool.loan_prices(
+        &borrow_info.borrow_mint,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        &clock,
+    )?;
+    let capacity = borrow_info.borrow_capacity(liquidity_pool.ltv_ratio, &borrow_price, &collateral_price)?;
+
+    if borrow_amount > capacity {
+        msg!("Requested {} but only {} can be borrowed against the posted collateral", borrow_amount, capacity);
+        return Err(ErrorCode::BorrowCapacityExceeded.into());
+    }
+
+    // As with a new loan, the full amount is owed but the origination fee is withheld from the disbursement
+    let origination_fee = liquidity_pool.fee_config.origination_fee(borrow_amount);
+    let disbursed_amount = borrow_amount - origination_fee;
+
+    liquidity_pool.sub_available(&borrow_info.borrow_mint, borrow_amount)?;
+
+    let (vault, fee_vault) = if borrow_info.borrow_mint == liquidity_pool.mint_a {
+        (&ctx.accounts.token_vault_a, &ctx.accounts.fee_vault_a)
+    } else {
+        (&ctx.accounts.token_vault_b, &ctx.accounts.fee_vault_b)
+    };
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        liquidity_pool.mint_a.as_ref(),
+        liquidity_pool.mint_b.as_ref(),
+        liquidity_pool.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        to: ctx.accounts.borrower_ata.to_account_info(),
+        authority: liquidity_pool.to_account_info(),
+        mint: loan_mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, disbursed_amount, loan_mint.decimals)?;
+
+    if origination_fee > 0 {
+        let cpi_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            to: fee_vault.to_account_info(),
+            authority: liquidity_pool.to_account_info(),
+            mint: loan_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, origination_fee, loan_mint.decimals)?;
+    }
+
+    borrow_info.total_borrowed = borrow_info
+        .total_borrowed
+        .checked_add(borrow_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    borrow_info.term_principal = borrow_info
+        .term_principal
+        .checked_add(borrow_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    borrow_info.origination_fee = borrow_info
+        .origination_fee
+        .checked_add(origination_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    liquidity_pool.add_borrowed(&borrow_info.borrow_mint, borrow_amount)?;
+    msg!(
+        "Pool utilization is now {} bps with {} left available",
+        liquidity_pool.utilization(&borrow_info.borrow_mint),
+        liquidity_pool.available(&borrow_info.borrow_mint)
+    );
+
+    msg!("Borrowed {} more on loan {}, debt now {}", borrow_amount, borrow_info.loan_id, borrow_info.total_debt()?);
+
+    Ok(())
+}
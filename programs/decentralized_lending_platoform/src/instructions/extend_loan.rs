@@ -0,0 +1,162 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::{BorrowDuration, BorrowInfo, LiquidityPool};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct ExtendLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(address = borrower_account_info.borrow_mint @ ErrorCode::InvalidMint)]
+    pub loan_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
+        bump,
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_b: Account<'info, TokenAccount>,
+
+    // Borrower's token account the interest and rollover fee are paid from
+    #[account(
+        mut,
+        associated_token::mint = loan_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_ata: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_extend_loan(ctx: Context<ExtendLoan>, borrow_duration: u8) -> Result<()> {
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrow_info = &mut ctx.accounts.borrower_account_info;
+    let loan_mint = &ctx.accounts.loan_mint;
+
+    let borrow_duration_value = BorrowDuration::from_u8(borrow_duration)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    liquidity_pool.accrue_interest(now)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrow_info.borrow_mint);
+    borrow_info.accrue_interest(borrow_index)?;
+
+    require!(
+        !borrow_info.is_closed && borrow_info.total_debt()? > 0,
+        ErrorCode::LoanNotActive
+    );
+    require!(
+        now <= borrow_info.expires_at + liquidity_pool.loan_terms.grace_period as i64,
+        ErrorCode::LoanPastGracePeriod
+    );
+
+    let (vault, fee_vault) = if borrow_info.borrow_mint == liquidity_pool.mint_a {
+        (&ctx.accounts.token_vault_a, &ctx.accounts.fee_vault_a)
+    } else {
+        (&ctx.accounts.token_vault_b, &ctx.accounts.fee_vault_b)
+    };
+
+    // Settle the interest accrued so far; the principal and collateral stay in place
+    let interest = borrow_info.accrued_interest;
+    borrow_info.apply_repayment(interest)?;
+    let reserves = liquidity_pool.collect_reserves(&borrow_info.borrow_mint, interest)?;
+    let pool_amount = interest - reserves;
+
+    let rollover_fee = (borrow_info.total_borrowed as u128 * liquidity_pool.fee_config.rollover_fee_bps as u128).div_ceil(10_000) as u64;
+    let fee_amount = reserves
+        .checked_add(rollover_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    if pool_amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.borrower_ata.to_account_info(),
+            to: vault.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+            mint: loan_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, pool_amount, loan_mint.decimals)?;
+    }
+
+    // The protocol's share of the interest and the rollover fee go to the fee vault
+    if fee_amount > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.borrower_ata.to_account_info(),
+            to: fee_vault.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+            mint: loan_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, fee_amount, loan_mint.decimals)?;
+    }
+
+    liquidity_pool.sub_borrowed(&borrow_info.borrow_mint, interest)?;
+    liquidity_pool.add_available(&borrow_info.borrow_mint, pool_amount)?;
+
+    // Start a fresh term from now
+    borrow_info.borrow_duration = borrow_duration_value;
+    borrow_info.start_term(now, borrow_index);
+    borrow_info.expires_at = now
+        .checked_add(borrow_duration_value.seconds())
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    msg!(
+        "Extended loan {}: paid {} interest and a {} rollover fee, now expires at {}",
+        borrow_info.loan_id,
+        interest,
+        rollover_fee,
+        borrow_info.expires_at
+    );
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::state::{BorrowInfo, LiquidityPool};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct FlagUnhealthyLoan<'info> {
+    /// CHECK: Only used to derive the borrower account
+    pub borrower: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
+        bump,
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+}
+
+/// Permissionless: records when a loan first crosses the liquidation threshold, starting its liquidation bonus auction,
+/// or clears the record if the loan has since become healthy again.
+pub fn process_flag_unhealthy_loan(ctx: Context<FlagUnhealthyLoan>) -> Result<()> {
+    let clock = Clock::get()?;
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrower_info = &mut ctx.accounts.borrower_account_info;
+
+    require!(
+        !borrower_info.is_closed && borrower_info.total_debt()? > 0,
+        ErrorCode::NothingToLiquidate
+    );
+
+    liquidity_pool.accrue_interest(clock.unix_timestamp)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrower_info.borrow_mint);
+    borrower_info.accrue_interest(borrow_index)?;
+
+    let (borrow_price, collateral_price) = liquidity_pool.loan_prices(
+        &borrower_info.borrow_mint,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        &clock,
+    )?;
+    let is_unhealthy = borrower_info.is_unhealthy(liquidity_pool.liquidation_threshold, &borrow_price, &collateral_price)?;
+
+    borrower_info.update_unhealthy_since(is_unhealthy, clock.unix_timestamp);
+
+    msg!("Loan {} unhealthy since: {}", borrower_info.loan_id, borrower_info.unhealthy_since);
+
+    Ok(())
+}
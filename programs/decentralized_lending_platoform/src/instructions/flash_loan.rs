@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::LiquidityPool;
+use crate::error::ErrorCode;
+
+// Positions of the accounts `flash_borrow` inspects in the matching `flash_repay` instruction
+const FLASH_REPAY_MINT_INDEX: usize = 1;
+const FLASH_REPAY_POOL_INDEX: usize = 2;
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(
+        constraint = token_mint.key() == liquidity_pool.mint_a || token_mint.key() == liquidity_pool.mint_b @ ErrorCode::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_b: Account<'info, TokenAccount>,
+
+    // Borrower's token account to receive the flash loan
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        associated_token::mint = token_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Instructions sysvar, used to find the matching flash repay
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(
+        constraint = token_mint.key() == liquidity_pool.mint_a || token_mint.key() == liquidity_pool.mint_b @ ErrorCode::InvalidMint
+    )]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub token_vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_b: Account<'info, TokenAccount>,
+
+    // Borrower's token account the loan and fee are repaid from
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_ata: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidLiquidityAmount);
+
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let token_mint = ctx.accounts.token_mint.key();
+    let instructions = ctx.accounts.instructions.to_account_info();
+
+    // Called through CPI, the top-level instruction would belong to another program
+    let current_index = load_current_index_checked(&instructions)? as usize;
+    let current_ix = load_instruction_at_checked(current_index, &instructions)?;
+    require_keys_eq!(current_ix.program_id, crate::ID, ErrorCode::FlashLoanCpiNotAllowed);
+
+    require!(!liquidity_pool.has_flash_loan(), ErrorCode::FlashLoanActive);
+
+    // The transaction must repay this exact loan later on, otherwise the borrow is rejected up front
+    let mut index = current_index + 1;
+    let repay_found = loop {
+        let Ok(ix) = load_instruction_at_checked(index, &instructions) else {
+            break false;
+        };
+
+        if ix.program_id == crate::ID
+            && ix.data.starts_with(crate::instruction::FlashRepay::DISCRIMINATOR)
+            && ix.data.get(8..16) == Some(&amount.to_le_bytes()[..])
+            && ix.accounts.get(FLASH_REPAY_MINT_INDEX).map(|meta| meta.pubkey) == Some(token_mint)
+            && ix.accounts.get(FLASH_REPAY_POOL_INDEX).map(|meta| meta.pubkey) == Some(liquidity_pool.key())
+        {
+            break true;
+        }
+
+        index += 1;
+    };
+
+    require!(repay_found, ErrorCode::FlashRepayNotFound);
+
+    let vault = if token_mint == liquidity_pool.mint_a {
+        liquidity_pool.flash_borrowed_a = amount;
+        &ctx.accounts.token_vault_a
+    } else {
+        liquidity_pool.flash_borrowed_b = amount;
+        &ctx.accounts.token_vault_b
+    };
+
+    // The loan leaves the vault for the rest of the transaction, so it is not available to anyone else until repaid
+    liquidity_pool.sub_available(&token_mint, amount)?;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        liquidity_pool.mint_a.as_ref(),
+        liquidity_pool.mint_b.as_ref(),
+        liquidity_pool.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    let lend_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: vault.to_account_info(),
+            to: ctx.accounts.borrower_ata.to_account_info(),
+            authority: liquidity_pool.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+
+    token::transfer_checked(lend_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+    msg!("Flash borrowed {} of mint {}", amount, token_mint);
+
+    Ok(())
+}
+
+pub fn process_flash_repay(ctx: Context<FlashRepay>, amount: u64) -> Result<()> {
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let token_mint = ctx.accounts.token_mint.key();
+
+    let (outstanding, vault, fee_vault) = if token_mint == liquidity_pool.mint_a {
+        (&mut liquidity_pool.flash_borrowed_a, &ctx.accounts.token_vault_a, &ctx.accounts.fee_vault_a)
+    } else {
+        (&mut liquidity_pool.flash_borrowed_b, &ctx.accounts.token_vault_b, &ctx.accounts.fee_vault_b)
+    };
+
+    require!(*outstanding > 0 && *outstanding == amount, ErrorCode::NoFlashLoanActive);
+    *outstanding = 0;
+    liquidity_pool.add_available(&token_mint, amount)?;
+
+    // Fee is rounded up so that small loans cannot dodge it
+    let fee = (amount as u128 * liquidity_pool.fee_config.flash_loan_fee_bps as u128).div_ceil(10_000);
+    let fee = u64::try_from(fee).map_err(|_| ErrorCode::MathOverflow)?;
+
+    let repay_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.borrower_ata.to_account_info(),
+            to: vault.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+        },
+    );
+
+    token::transfer_checked(repay_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+    if fee > 0 {
+        let fee_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.borrower_ata.to_account_info(),
+                to: fee_vault.to_account_info(),
+                authority: ctx.accounts.borrower.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+            },
+        );
+
+        token::transfer_checked(fee_ctx, fee, ctx.accounts.token_mint.decimals)?;
+    }
+
+    msg!("Flash repaid {} of mint {} with a {} fee", amount, token_mint, fee);
+
+    Ok(())
+}
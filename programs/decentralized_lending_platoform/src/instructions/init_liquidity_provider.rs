@@ -1,16 +1,27 @@
 use anchor_lang::prelude::*;
-use crate::state::LiquidityProviderInfo;
+use crate::state::{LiquidityPool, LiquidityProviderInfo};
 
 #[derive(Accounts)]
 pub struct InitLiquidityProvider<'info> {
     #[account(mut)]
     pub provider: Signer<'info>,
 
+    #[account(
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
     #[account(
         init,
         payer = provider,
         space = 8 + LiquidityProviderInfo::INIT_SPACE,
-        seeds = [b"liquidity_provider", provider.key().as_ref()],
+        seeds = [b"liquidity_provider", liquidity_pool.key().as_ref(), provider.key().as_ref()],
         bump
     )]
     pub liquidity_provider_account: Account<'info, LiquidityProviderInfo>,
@@ -18,13 +29,13 @@ pub struct InitLiquidityProvider<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize_liquidity_provider(ctx: Context<InitLiquidityProvider>) -> Result<()> {
+pub fn process_initialize_liquidity_provider(ctx: Context<InitLiquidityProvider>) -> Result<()> {
 
     let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
 
     liquidity_provider_account.set_inner(LiquidityProviderInfo {
         provider: ctx.accounts.provider.key(),
-        liquidity_pool: Pubkey::default(),
+        liquidity_pool: ctx.accounts.liquidity_pool.key(),
         provided_token_a: 0,
         provided_token_b: 0,
         total_liquidity_provided: 0,
@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::LiquidityPool;
+use crate::{
+    FeeConfig, InterestRateModel, LiquidationBonus, LiquidityPool, LoanTerms, DEFAULT_CLOSE_FACTOR, DEFAULT_FLASH_LOAN_FEE_BPS,
+    DEFAULT_GRACE_PERIOD_SECONDS, DEFAULT_MAX_CONFIDENCE_BPS, DEFAULT_MAX_EMA_DEVIATION_BPS, DEFAULT_MAX_PRICE_AGE_SECONDS,
+    DEFAULT_MAX_PRICE_AGE_SLOTS, DEFAULT_MIN_INTEREST_PERIOD_SECONDS, DEFAULT_ORIGINATION_FEE_BPS, DEFAULT_RESERVE_FACTOR_BPS,
+    DEFAULT_ROLLOVER_FEE_BPS, INDEX_SCALE,
+};
 use crate::error::ErrorCode;
+use crate::oracle::{OracleConfig, OraclePrice};
 
 #[derive(Accounts)]
 pub struct InitializeLiquidityPool<'info> {
@@ -72,6 +78,32 @@ pub struct InitializeLiquidityPool<'info> {
     )]
     pub fee_vault_b: Account<'info, TokenAccount>,
 
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"collateral_vault_a", token_mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = liquidity_pool
+    )]
+    pub collateral_vault_a: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"collateral_vault_b", token_mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint_b,
+        token::authority = liquidity_pool
+    )]
+    pub collateral_vault_b: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated as a Pyth-format price account in the handler
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a Pyth-format price account in the handler
+    pub oracle_b: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
@@ -89,7 +121,45 @@ pub struct SetPoolParameters<'info> {
     pub liquidity_pool: Account<'info, LiquidityPool>,
 }
 
-pub fn initialize_liquidity_pool(ctx: Context<InitializeLiquidityPool>, ltv_ratio: u8, liquidation_threshold: u8, liquidation_penalty: u8, interest_rate: u8) -> Result<()> {
+#[derive(Accounts)]
+pub struct SetPoolOracles<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_pool", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.mint_b.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    /// CHECK: Validated as a Pyth-format price account in the handler
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Validated as a Pyth-format price account in the handler
+    pub oracle_b: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolTreasury<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_pool", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.mint_b.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(token::mint = liquidity_pool.mint_a)]
+    pub treasury_a: Account<'info, TokenAccount>,
+
+    #[account(token::mint = liquidity_pool.mint_b)]
+    pub treasury_b: Account<'info, TokenAccount>,
+}
+
+pub fn process_initialize_liquidity_pool(ctx: Context<InitializeLiquidityPool>, ltv_ratio: u8, liquidation_threshold: u8, liquidation_bonus: LiquidationBonus, interest_rate_model: InterestRateModel) -> Result<()> {
 
     require_keys_neq!(ctx.accounts.token_mint_a.key(), ctx.accounts.token_mint_b.key(), ErrorCode::SameTokenMints);
 
@@ -103,34 +173,79 @@ pub fn initialize_liquidity_pool(ctx: Context<InitializeLiquidityPool>, ltv_rati
         return Err(ErrorCode::InvalidLiquidationThreshold.into());
     }
 
-    if liquidation_penalty == 0 || liquidation_penalty > 100 {
-        return Err(ErrorCode::InvalidLiquidationPanelty.into());
-    }
+    liquidation_bonus.validate()?;
+    interest_rate_model.validate()?;
+
+    OraclePrice::load(&ctx.accounts.oracle_a)?;
+    OraclePrice::load(&ctx.accounts.oracle_b)?;
+
+    let now = Clock::get()?.unix_timestamp;
 
     liquidity_pool_account.set_inner(LiquidityPool {
         authority: ctx.accounts.creator.key(),
         mint_a: ctx.accounts.token_mint_a.key(),
         mint_b: ctx.accounts.token_mint_b.key(),
+        decimals_a: ctx.accounts.token_mint_a.decimals,
+        decimals_b: ctx.accounts.token_mint_b.decimals,
         lp_mint: ctx.accounts.lp_token_mint.key(),
         vault_a: ctx.accounts.token_vault_a.key(),
         vault_b: ctx.accounts.token_vault_b.key(),
         fees_vault_a: ctx.accounts.fee_vault_a.key(),
         fees_vault_b: ctx.accounts.fee_vault_b.key(),
-        total_liquidity: 0,
+        collateral_vault_a: ctx.accounts.collateral_vault_a.key(),
+        collateral_vault_b: ctx.accounts.collateral_vault_b.key(),
+        treasury_a: Pubkey::default(),
+        treasury_b: Pubkey::default(),
+        max_fee_withdrawal_a: 0,
+        max_fee_withdrawal_b: 0,
+        oracle_a: ctx.accounts.oracle_a.key(),
+        oracle_b: ctx.accounts.oracle_b.key(),
+        oracle_config: OracleConfig {
+            max_price_age_slots: DEFAULT_MAX_PRICE_AGE_SLOTS,
+            max_price_age_seconds: DEFAULT_MAX_PRICE_AGE_SECONDS,
+            max_confidence_bps: DEFAULT_MAX_CONFIDENCE_BPS,
+            max_ema_deviation_bps: DEFAULT_MAX_EMA_DEVIATION_BPS,
+        },
+        total_deposited_a: 0,
+        total_deposited_b: 0,
+        available_a: 0,
+        available_b: 0,
         total_borrowed_a: 0,
         total_borrowed_b: 0,
         total_borrowed: 0,
+        reserves_owed_a: 0,
+        reserves_owed_b: 0,
+        reserves_collected_a: 0,
+        reserves_collected_b: 0,
         ltv_ratio,
         liquidation_threshold,
-        liquidation_penalty,
-        interest_rate,
-        created_at: Clock::get()?.unix_timestamp,
+        loan_terms: LoanTerms {
+            grace_period: DEFAULT_GRACE_PERIOD_SECONDS,
+            min_interest_period: DEFAULT_MIN_INTEREST_PERIOD_SECONDS,
+        },
+        close_factor: DEFAULT_CLOSE_FACTOR,
+        liquidation_bonus,
+        interest_rate_model,
+        fee_config: FeeConfig {
+            flash_loan_fee_bps: DEFAULT_FLASH_LOAN_FEE_BPS,
+            reserve_factor_bps: DEFAULT_RESERVE_FACTOR_BPS,
+            origination_fee_bps: DEFAULT_ORIGINATION_FEE_BPS,
+            rollover_fee_bps: DEFAULT_ROLLOVER_FEE_BPS,
+        },
+        flash_borrowed_a: 0,
+        flash_borrowed_b: 0,
+        borrow_index_a: INDEX_SCALE,
+        borrow_index_b: INDEX_SCALE,
+        last_accrual_timestamp: now,
+        created_at: now,
         lp_supply: 0,
         bump: ctx.bumps.liquidity_pool,
         vault_a_bump: ctx.bumps.token_vault_a,
         vault_b_bump: ctx.bumps.token_vault_b,
         fees_vault_a_bump: ctx.bumps.fee_vault_a,
-        fees_vault_b_bump: ctx.bumps.fee_vault_b
+        fees_vault_b_bump: ctx.bumps.fee_vault_b,
+        collateral_vault_a_bump: ctx.bumps.collateral_vault_a,
+        collateral_vault_b_bump: ctx.bumps.collateral_vault_b
     });
 
     msg!("Liquidity pool initialized successfully!");
@@ -144,22 +259,80 @@ pub fn set_pool_parameters(
     ctx: Context<SetPoolParameters>, 
     new_ltv_ratio: u8,
     new_liquidation_threshold: u8,
-    new_liquidation_penalty: u8,
-    new_interest_rate: u8,
+    new_liquidation_bonus: LiquidationBonus,
+    new_interest_rate_model: InterestRateModel,
+    new_close_factor: u8,
+    new_loan_terms: LoanTerms,
 ) -> Result<()> {
 
     require_eq!(ctx.accounts.creator.key(), ctx.accounts.liquidity_pool.authority, ErrorCode::InvalidAuthority);
     require!(new_ltv_ratio <= new_liquidation_threshold, ErrorCode::InvalidLtvThreshold);
-    require!(new_liquidation_penalty < 100, ErrorCode::InvalidPenalty);
-    require!(new_interest_rate <= 100, ErrorCode::InvalidInterestRate);
+    new_liquidation_bonus.validate()?;
+    new_interest_rate_model.validate()?;
+    require!(new_close_factor > 0 && new_close_factor <= 100, ErrorCode::InvalidCloseFactor);
 
     let pool = &mut ctx.accounts.liquidity_pool;
+
+    // Settle interest owed at the old rate before switching to the new one
+    pool.accrue_interest(Clock::get()?.unix_timestamp)?;
+
     pool.ltv_ratio = new_ltv_ratio;
     pool.liquidation_threshold = new_liquidation_threshold;
-    pool.liquidation_penalty = new_liquidation_penalty;
-    pool.interest_rate = new_interest_rate;
+    pool.liquidation_bonus = new_liquidation_bonus;
+    pool.interest_rate_model = new_interest_rate_model;
+    pool.close_factor = new_close_factor;
+    pool.loan_terms = new_loan_terms;
 
     msg!("Updated pool info: {:?}", ctx.accounts.liquidity_pool);
 
     Ok(())
 }
+
+pub fn set_pool_fees(ctx: Context<SetPoolParameters>, fee_config: FeeConfig) -> Result<()> {
+
+    require_eq!(ctx.accounts.creator.key(), ctx.accounts.liquidity_pool.authority, ErrorCode::InvalidAuthority);
+    fee_config.validate()?;
+
+    let pool = &mut ctx.accounts.liquidity_pool;
+
+    pool.accrue_interest(Clock::get()?.unix_timestamp)?;
+
+    pool.fee_config = fee_config;
+
+    msg!("Updated pool fees: {:?}", pool.fee_config);
+
+    Ok(())
+}
+
+pub fn set_pool_treasury(ctx: Context<SetPoolTreasury>, max_fee_withdrawal_a: u64, max_fee_withdrawal_b: u64) -> Result<()> {
+
+    require_eq!(ctx.accounts.creator.key(), ctx.accounts.liquidity_pool.authority, ErrorCode::InvalidAuthority);
+
+    let pool = &mut ctx.accounts.liquidity_pool;
+
+    pool.treasury_a = ctx.accounts.treasury_a.key();
+    pool.treasury_b = ctx.accounts.treasury_b.key();
+    pool.max_fee_withdrawal_a = max_fee_withdrawal_a;
+    pool.max_fee_withdrawal_b = max_fee_withdrawal_b;
+
+    msg!("Updated pool treasury: {} / {}", pool.treasury_a, pool.treasury_b);
+
+    Ok(())
+}
+
+pub fn set_pool_oracles(ctx: Context<SetPoolOracles>, oracle_config: OracleConfig) -> Result<()> {
+
+    require_eq!(ctx.accounts.creator.key(), ctx.accounts.liquidity_pool.authority, ErrorCode::InvalidAuthority);
+
+    OraclePrice::load(&ctx.accounts.oracle_a)?;
+    OraclePrice::load(&ctx.accounts.oracle_b)?;
+
+    let pool = &mut ctx.accounts.liquidity_pool;
+    pool.oracle_a = ctx.accounts.oracle_a.key();
+    pool.oracle_b = ctx.accounts.oracle_b.key();
+    pool.oracle_config = oracle_config;
+
+    msg!("Updated pool oracles: {:?}", ctx.accounts.liquidity_pool);
+
+    Ok(())
+}
@@ -2,7 +2,6 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TransferChecked, Mint, TokenAccount};
 use crate::state::{BorrowInfo, LiquidityPool};
 use crate::error::ErrorCode;
-use crate::BorrowDuration;
 use anchor_spl::associated_token::AssociatedToken;
 
 #[derive(Accounts)]
@@ -10,7 +9,8 @@ pub struct Liquidate<'info> {
     #[account(mut)]
     pub liquidator: Signer<'info>,
 
-    pub borrower: AccountInfo<'info>,
+    /// CHECK: Only used to derive the borrower account and receive any collateral left after liquidation
+    pub borrower: UncheckedAccount<'info>,
 
     pub loan_mint: Account<'info, Mint>,
 
@@ -18,9 +18,11 @@ pub struct Liquidate<'info> {
 
     #[account(
         mut,
-        seeds = [b"borrower_account", borrower.key().as_ref()],
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
         bump,
-        constraint = borrower_account_info.borrowed_from_pool == liquidity_pool.key()
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+        constraint = borrower_account_info.borrow_mint == loan_mint.key() @ ErrorCode::InvalidMint,
+        constraint = borrower_account_info.collateral_mint == collateral_mint.key() @ ErrorCode::InvalidMint,
     )]
     pub borrower_account_info: Account<'info, BorrowInfo>,
 
@@ -36,7 +38,7 @@ pub struct Liquidate<'info> {
     )]
     pub liquidity_pool: Account<'info, LiquidityPool>,
 
-   #[account(
+    #[account(
         mut,
         seeds = [b"token_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
         bump,
@@ -54,6 +56,24 @@ pub struct Liquidate<'info> {
     )]
     pub token_vault_b: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_b: Account<'info, TokenAccount>,
+
     #[account(
         mut,
         seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
@@ -72,10 +92,22 @@ pub struct Liquidate<'info> {
     )]
     pub fee_vault_b: Account<'info, TokenAccount>,
 
-    // Borrower's token accounts
-    #[account(mut)]
+    // Borrower's token account to receive any collateral left after liquidation
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = borrower,
+    )]
     pub borrower_collateral_ata: Account<'info, TokenAccount>,
 
+    // Liquidator's token account the debt is repaid from
+    #[account(
+        mut,
+        associated_token::mint = loan_mint,
+        associated_token::authority = liquidator,
+    )]
+    pub liquidator_ata: Account<'info, TokenAccount>,
+
     // Liquidator's token account to receive seized collateral
     #[account(
         mut,
@@ -84,54 +116,181 @@ pub struct Liquidate<'info> {
     )]
     pub liquidator_collateral_ata: Account<'info, TokenAccount>,
 
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handler(ctx: Context<Liquidate>) -> Result<()> {
+pub fn process_liquidate(ctx: Context<Liquidate>, repay_amount: u64) -> Result<()> {
     let clock = Clock::get()?;
-    let borrower_info = &ctx.accounts.borrower_account_info;
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrower_info = &mut ctx.accounts.borrower_account_info;
 
-    // Validate that the loan has expired
+    liquidity_pool.accrue_interest(clock.unix_timestamp)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrower_info.borrow_mint);
+    borrower_info.accrue_interest(borrow_index)?;
 
-    let borrow_duration = borrower_info.borrow_duration;
+    require!(
+        !borrower_info.is_closed && borrower_info.total_debt()? > 0,
+        ErrorCode::NothingToLiquidate
+    );
+
+    // An expired loan only becomes liquidatable once the pool's grace period to repay it has passed
+    let expiry_time = borrower_info.expires_at + liquidity_pool.loan_terms.grace_period as i64;
+    let is_expired = clock.unix_timestamp > expiry_time;
+    let (borrow_price, collateral_price) = liquidity_pool.loan_prices(
+        &borrower_info.borrow_mint,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        &clock,
+    )?;
+    let is_unhealthy = borrower_info.is_unhealthy(liquidity_pool.liquidation_threshold, &borrow_price, &collateral_price)?;
+
+    require!(is_expired || is_unhealthy, ErrorCode::PositionNotLiquidatable);
+
+    borrower_info.update_unhealthy_since(is_unhealthy, clock.unix_timestamp);
+
+    // The bonus auction runs from when the position first became unhealthy, or from expiry for a healthy expired loan
+    let auction_start = if is_unhealthy { borrower_info.unhealthy_since } else { expiry_time };
+    let bonus_bps = liquidity_pool.liquidation_bonus.bonus_bps(clock.unix_timestamp - auction_start);
+
+    let debt = borrower_info.total_debt()?;
 
-    let borrow_duration_u8 = match borrow_duration {
-        BorrowDuration::TenDays => 10,
-        BorrowDuration::TwentyDays => 20,
-        BorrowDuration::ThirtyDays => 30,
-        _ => 10
+    // An unhealthy position can only be repaid up to the close factor per call, leaving the rest open;
+    // an expired loan is past its term and can be closed out entirely
+    let max_repay = if is_expired {
+        debt
+    } else {
+        ((debt as u128 * liquidity_pool.close_factor as u128 / 100) as u64).max(1)
     };
+    let repay_amount = repay_amount.min(max_repay);
 
-    let expiry_time = borrower_info.borrowed_at + borrow_duration_u8;
-    require!(
-        clock.unix_timestamp > expiry_time,
-        ErrorCode::LoanNotExpired
+    require!(repay_amount > 0, ErrorCode::InvalidRepayAmount);
+
+    // Liquidator receives collateral worth the repaid debt plus the current auction bonus, capped by the posted collateral
+    let seized_value = borrow_price
+        .value_of(repay_amount)?
+        .checked_mul(10_000 + bonus_bps as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / 10_000;
+    let seized_collateral = collateral_price
+        .amount_for(seized_value)?
+        .min(borrower_info.total_collateral);
+
+    let remaining_debt = debt - repay_amount;
+    let remaining_collateral = borrower_info
+        .total_collateral
+        .checked_sub(seized_collateral)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let (loan_vault, loan_fee_vault, collateral_vault) = if borrower_info.borrow_mint == liquidity_pool.mint_a {
+        (&ctx.accounts.token_vault_a, &ctx.accounts.fee_vault_a, &ctx.accounts.collateral_vault_b)
+    } else {
+        (&ctx.accounts.token_vault_b, &ctx.accounts.fee_vault_b, &ctx.accounts.collateral_vault_a)
+    };
+
+    let interest_paid = borrower_info.apply_repayment(repay_amount)?;
+    let reserves = liquidity_pool.collect_reserves(&borrower_info.borrow_mint, interest_paid)?;
+    let pool_amount = repay_amount - reserves;
+
+    let repay_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.liquidator_ata.to_account_info(),
+            to: loan_vault.to_account_info(),
+            authority: ctx.accounts.liquidator.to_account_info(),
+            mint: ctx.accounts.loan_mint.to_account_info(),
+        },
     );
-    
-    require_keys_eq!(borrower_info.borrower, ctx.accounts.borrower.key(), ErrorCode::InvalidBorrower);
 
-    let amount = borrower_info.total_collateral;
+    token::transfer_checked(repay_ctx, pool_amount, ctx.accounts.loan_mint.decimals)?;
+
+    // The protocol's share of the repaid interest goes to the fee vault
+    if reserves > 0 {
+        let reserves_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.liquidator_ata.to_account_info(),
+                to: loan_fee_vault.to_account_info(),
+                authority: ctx.accounts.liquidator.to_account_info(),
+                mint: ctx.accounts.loan_mint.to_account_info(),
+            },
+        );
 
-    let cpi_ctx = CpiContext::new(
+        token::transfer_checked(reserves_ctx, reserves, ctx.accounts.loan_mint.decimals)?;
+    }
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        liquidity_pool.mint_a.as_ref(),
+        liquidity_pool.mint_b.as_ref(),
+        liquidity_pool.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    let seize_ctx = CpiContext::new_with_signer(
         ctx.accounts.token_program.to_account_info(),
         TransferChecked {
-            from: ctx.accounts.borrower_collateral_ata.to_account_info(),
+            from: collateral_vault.to_account_info(),
             to: ctx.accounts.liquidator_collateral_ata.to_account_info(),
-            authority: ctx.accounts.borrower.to_account_info(),
+            authority: liquidity_pool.to_account_info(),
             mint: ctx.accounts.collateral_mint.to_account_info(),
         },
+        signer_seeds,
     );
 
-    token::transfer_checked(cpi_ctx, amount, ctx.accounts.collateral_mint.decimals)?;
+    token::transfer_checked(seize_ctx, seized_collateral, ctx.accounts.collateral_mint.decimals)?;
+
+    // Once the debt is gone, whatever collateral is left goes back to the borrower
+    if remaining_debt == 0 && remaining_collateral > 0 {
+        let return_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: collateral_vault.to_account_info(),
+                to: ctx.accounts.borrower_collateral_ata.to_account_info(),
+                authority: liquidity_pool.to_account_info(),
+                mint: ctx.accounts.collateral_mint.to_account_info(),
+            },
+            signer_seeds,
+        );
 
-    // Reset borrow info
-    let mut borrower_info = &mut ctx.accounts.borrower_account_info;
-    borrower_info.total_borrowed = 0;
-    borrower_info.total_collateral = 0;
-    borrower_info.borrowed_at = 0;
+        token::transfer_checked(return_ctx, remaining_collateral, ctx.accounts.collateral_mint.decimals)?;
+    }
+
+    liquidity_pool.sub_borrowed(&borrower_info.borrow_mint, repay_amount)?;
+
+    liquidity_pool.add_available(&borrower_info.borrow_mint, pool_amount)?;
+
+    if remaining_debt == 0 {
+        // Close out the liquidated position
+        borrower_info.total_collateral = 0;
+        borrower_info.borrowed_at = 0;
+        borrower_info.unhealthy_since = 0;
+        borrower_info.is_closed = true;
+    } else {
+        borrower_info.total_collateral = remaining_collateral;
+
+        // A partial liquidation that restores the position's health ends its auction
+        let is_unhealthy = borrower_info.is_unhealthy(liquidity_pool.liquidation_threshold, &borrow_price, &collateral_price)?;
+        borrower_info.update_unhealthy_since(is_unhealthy, clock.unix_timestamp);
+    }
+
+    msg!(
+        "Liquidated position: repaid {} at a {} bps bonus, seized {} collateral, remaining debt {}, remaining collateral {}",
+        repay_amount,
+        bonus_bps,
+        seized_collateral,
+        remaining_debt,
+        borrower_info.total_collateral
+    );
 
     Ok(())
 }
@@ -7,14 +7,38 @@ pub use initialize_pool::*;
 pub mod provide_liquidity;
 pub use provide_liquidity::*;
 
+pub mod withdraw_liquidity;
+pub use withdraw_liquidity::*;
+
 pub mod init_liquidity_provider;
 pub use init_liquidity_provider::*;
 
 pub mod borrow_funds;
 pub use borrow_funds::*;
 
+pub mod borrow_more;
+pub use borrow_more::*;
+
 pub mod repay_funds;
 pub use repay_funds::*;
 
+pub mod extend_loan;
+pub use extend_loan::*;
+
+pub mod add_collateral;
+pub use add_collateral::*;
+
+pub mod withdraw_collateral;
+pub use withdraw_collateral::*;
+
 pub mod liquidate;
-pub use liquidate::*;
\ No newline at end of file
+pub use liquidate::*;
+
+pub mod flag_unhealthy_loan;
+pub use flag_unhealthy_loan::*;
+
+pub mod flash_loan;
+pub use flash_loan::*;
+
+pub mod withdraw_protocol_fees;
+pub use withdraw_protocol_fees::*;
\ No newline at end of file
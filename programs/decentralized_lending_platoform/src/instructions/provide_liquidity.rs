@@ -34,8 +34,9 @@ pub struct ProvideLiquidity<'info,> {
 
      #[account(
         mut,
-        seeds = [b"liquidity_provider", provider.key().as_ref()],
-        bump
+        seeds = [b"liquidity_provider", liquidity_pool.key().as_ref(), provider.key().as_ref()],
+        bump,
+        has_one = liquidity_pool @ ErrorCode::InvalidLiquidityPool
     )]
     pub liquidity_provider_account: Account<'info, LiquidityProviderInfo>,
 
@@ -78,6 +79,14 @@ pub struct ProvideLiquidity<'info,> {
     )]
     pub provider_lp_mint_ata: Account<'info, TokenAccount>,
 
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 
     pub token_program: Program<'info, Token>,
@@ -85,36 +94,67 @@ pub struct ProvideLiquidity<'info,> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn provide_liquidity(ctx: Context<ProvideLiquidity>, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+pub fn process_provide_liquidity(ctx: Context<ProvideLiquidity>, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
 
-    require!(token_a_amount == token_b_amount, ErrorCode::InvalidLiquidityAmount);
+    // Either side may be deposited on its own; LP tokens are minted for the combined value
+    require!(token_a_amount > 0 || token_b_amount > 0, ErrorCode::InvalidLiquidityAmount);
 
     let liquidity_pool_account = &mut ctx.accounts.liquidity_pool;
     let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
 
-    let cpi_accounts_1 = TransferChecked {
-        from: ctx.accounts.provider_token_a_ata.to_account_info(),
-        to: ctx.accounts.token_vault_a.to_account_info(),
-        authority: ctx.accounts.provider.to_account_info(),
-        mint: ctx.accounts.token_mint_a.to_account_info(),
-    };
+    // Liquidity cannot move in or out of the vaults while a flash loan is out
+    require!(!liquidity_pool_account.has_flash_loan(), ErrorCode::FlashLoanActive);
 
-    let cpi_context1 = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_1);
+    let clock = Clock::get()?;
+    liquidity_pool_account.accrue_interest(clock.unix_timestamp)?;
 
-    transfer_checked(cpi_context1, token_a_amount, ctx.accounts.token_mint_a.decimals)?;
+    // Both sides are valued in USD so deposits of differently priced mints, with different decimals, mint fair shares
+    let (price_a, price_b) = liquidity_pool_account.load_prices(&ctx.accounts.oracle_a, &ctx.accounts.oracle_b, &clock)?;
 
-    let cpi_accounts_2 = TransferChecked {
-        from: ctx.accounts.provider_token_b_ata.to_account_info(),
-        to: ctx.accounts.token_vault_b.to_account_info(),
-        authority: ctx.accounts.provider.to_account_info(),
-        mint: ctx.accounts.token_mint_b.to_account_info()
+    // Value the pool before the deposit lands so existing LPs keep the interest they have earned
+    let pool_value = liquidity_pool_account.total_value(&price_a, &price_b)?;
+    let deposit_value = price_a
+        .value_of(token_a_amount)?
+        .checked_add(price_b.value_of(token_b_amount)?)
+        .ok_or(ErrorCode::Overflow)?;
+
+    let lp_tokens_to_mint = if liquidity_pool_account.lp_supply == 0 || pool_value == 0 {
+        deposit_value
+    } else {
+        deposit_value
+            .checked_mul(liquidity_pool_account.lp_supply as u128)
+            .ok_or(ErrorCode::Overflow)?
+            / pool_value
     };
+    let lp_tokens_to_mint = u64::try_from(lp_tokens_to_mint).map_err(|_| ErrorCode::Overflow)?;
+
+    require!(lp_tokens_to_mint > 0, ErrorCode::InvalidLiquidityAmount);
+
+    if token_a_amount > 0 {
+        let cpi_accounts_1 = TransferChecked {
+            from: ctx.accounts.provider_token_a_ata.to_account_info(),
+            to: ctx.accounts.token_vault_a.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+            mint: ctx.accounts.token_mint_a.to_account_info(),
+        };
+
+        let cpi_context1 = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_1);
 
-    let cpi_context_2 = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_2);
+        transfer_checked(cpi_context1, token_a_amount, ctx.accounts.token_mint_a.decimals)?;
+    }
 
-    transfer_checked(cpi_context_2, token_b_amount, ctx.accounts.token_mint_b.decimals)?;
+    if token_b_amount > 0 {
+        let cpi_accounts_2 = TransferChecked {
+            from: ctx.accounts.provider_token_b_ata.to_account_info(),
+            to: ctx.accounts.token_vault_b.to_account_info(),
+            authority: ctx.accounts.provider.to_account_info(),
+            mint: ctx.accounts.token_mint_b.to_account_info()
+        };
 
-    let lp_tokens_to_mint = token_a_amount + token_b_amount;
+        let cpi_context_2 = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts_2);
+
+        transfer_checked(cpi_context_2, token_b_amount, ctx.accounts.token_mint_b.decimals)?;
+    }
 
     let token_mint_a = ctx.accounts.token_mint_a.key();
     let token_mint_b = ctx.accounts.token_mint_b.key();
@@ -137,12 +177,23 @@ pub fn provide_liquidity(ctx: Context<ProvideLiquidity>, token_a_amount: u64, to
 
     mint_to(mint_context, lp_tokens_to_mint)?;
 
-    liquidity_pool_account.total_liquidity = liquidity_pool_account
-        .total_liquidity
-        .checked_add(token_a_amount + token_b_amount)
+    liquidity_pool_account.total_deposited_a = liquidity_pool_account
+        .total_deposited_a
+        .checked_add(token_a_amount)
+        .ok_or(ErrorCode::Overflow)?;
+
+    liquidity_pool_account.total_deposited_b = liquidity_pool_account
+        .total_deposited_b
+        .checked_add(token_b_amount)
         .ok_or(ErrorCode::Overflow)?;
 
-    liquidity_provider_account.liquidity_pool = liquidity_pool_account.key();
+    liquidity_pool_account.add_available(&token_mint_a, token_a_amount)?;
+    liquidity_pool_account.add_available(&token_mint_b, token_b_amount)?;
+
+    liquidity_pool_account.lp_supply = liquidity_pool_account
+        .lp_supply
+        .checked_add(lp_tokens_to_mint)
+        .ok_or(ErrorCode::Overflow)?;
 
     liquidity_provider_account.provided_token_a = liquidity_provider_account
         .provided_token_a
@@ -156,8 +207,8 @@ pub fn provide_liquidity(ctx: Context<ProvideLiquidity>, token_a_amount: u64, to
 
     liquidity_provider_account.total_liquidity_provided = liquidity_provider_account
         .total_liquidity_provided
-        .checked_add(token_a_amount + token_b_amount)
-        .ok_or(ErrorCode::Overflow)?;
+        .checked_add(u64::try_from(deposit_value).map_err(|_| ErrorCode::MathOverflow)?)
+        .ok_or(ErrorCode::MathOverflow)?;
 
     liquidity_provider_account.total_lp_tokens = liquidity_provider_account
         .total_lp_tokens
@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
 use anchor_spl::associated_token::AssociatedToken;
 
-use crate::{BorrowDuration, BorrowInfo, LiquidityPool};
+use crate::{BorrowInfo, LiquidityPool};
 use crate::error::ErrorCode;
 
 
@@ -16,7 +16,7 @@ pub struct RepayFunds<'info> {
 
     #[account(
         mut,
-        seeds = [b"borrower_account", borrower.key().as_ref()],
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
         bump
     )]
     pub borrower_account_info: Account<'info, BorrowInfo>,
@@ -51,6 +51,24 @@ pub struct RepayFunds<'info> {
     )]
     pub token_vault_b: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_b: Account<'info, TokenAccount>,
+
     #[account(
         mut,
         seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
@@ -89,32 +107,64 @@ pub struct RepayFunds<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
+pub fn process_repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
     let borrow_info = &mut ctx.accounts.borrower_account_info;
 
-    require!(
-        repay_amount <= borrow_info.total_borrowed,
-        ErrorCode::InvalidRepayAmount
-    );
+    let now = Clock::get()?.unix_timestamp;
+    liquidity_pool.accrue_interest(now)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrow_info.borrow_mint);
+    borrow_info.accrue_interest(borrow_index)?;
+
+    // Closing the loan within the pool's minimum interest period still pays interest for the whole period,
+    // so the amount that closes it includes the top-up
+    let rate_bps = liquidity_pool.borrow_rate_bps(&borrow_info.borrow_mint);
+    let minimum_interest = borrow_info.minimum_interest_top_up(
+        now,
+        liquidity_pool.loan_terms.min_interest_period,
+        borrow_index,
+        rate_bps,
+    )?;
+    let closing_amount = borrow_info
+        .total_debt()?
+        .checked_add(minimum_interest)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Debt keeps accruing until the transaction lands, so any amount above it, e.g. u64::MAX, repays the loan in full
+    let repay_amount = repay_amount.min(closing_amount);
+    require!(repay_amount > 0, ErrorCode::InvalidRepayAmount);
+
+    if repay_amount == closing_amount && minimum_interest > 0 {
+        msg!("Loan closed early, adding {} interest to reach the minimum interest period", minimum_interest);
+        borrow_info.accrued_interest = borrow_info
+            .accrued_interest
+            .checked_add(minimum_interest)
+            .ok_or(ErrorCode::MathOverflow)?;
+        liquidity_pool.add_interest(&borrow_info.borrow_mint, minimum_interest)?;
+    }
 
     // Determine repayment vault and mint
-    let (repay_vault, repay_mint, repay_mint_info, repay_decimals) =
-        if borrow_info.borrowed_from_pool == ctx.accounts.liquidity_pool.mint_a {
+    let (repay_vault, repay_fee_vault, repay_mint_info, repay_decimals) =
+        if borrow_info.borrow_mint == liquidity_pool.mint_a {
             (
                 &ctx.accounts.token_vault_a,
-                &ctx.accounts.wanted_mint,
+                &ctx.accounts.fee_vault_a,
                 &ctx.accounts.wanted_mint,
                 ctx.accounts.wanted_mint.decimals,
             )
         } else {
             (
                 &ctx.accounts.token_vault_b,
-                &ctx.accounts.wanted_mint,
+                &ctx.accounts.fee_vault_b,
                 &ctx.accounts.wanted_mint,
                 ctx.accounts.wanted_mint.decimals,
             )
         };
 
+    let interest_paid = borrow_info.apply_repayment(repay_amount)?;
+    let reserves = liquidity_pool.collect_reserves(&borrow_info.borrow_mint, interest_paid)?;
+    let pool_amount = repay_amount - reserves;
+
     // Transfer repayment to the vault
     let cpi_accounts = TransferChecked {
         from: ctx.accounts.borrower_ata.to_account_info(),
@@ -123,22 +173,35 @@ pub fn repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
         mint: repay_mint_info.to_account_info(),
     };
     let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    transfer_checked(cpi_ctx, repay_amount, repay_decimals)?;
+    transfer_checked(cpi_ctx, pool_amount, repay_decimals)?;
+
+    // The protocol's share of the interest goes to the fee vault
+    if reserves > 0 {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.borrower_ata.to_account_info(),
+            to: repay_fee_vault.to_account_info(),
+            authority: ctx.accounts.borrower.to_account_info(),
+            mint: repay_mint_info.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, reserves, repay_decimals)?;
+    }
 
-    borrow_info.total_borrowed -= repay_amount;
+    liquidity_pool.sub_borrowed(&borrow_info.borrow_mint, repay_amount)?;
+    liquidity_pool.add_available(&borrow_info.borrow_mint, pool_amount)?;
 
-    // If loan fully repaid, return collateral
-    if borrow_info.total_borrowed == 0 {
+    // If loan fully repaid, return collateral from the pool's collateral vault
+    if borrow_info.total_debt()? == 0 {
         let (collateral_vault, collateral_mint, collateral_decimals) =
-            if borrow_info.borrowed_from_pool == ctx.accounts.liquidity_pool.mint_a {
+            if borrow_info.borrow_mint == liquidity_pool.mint_a {
                 (
-                    &ctx.accounts.fee_vault_b,
+                    &ctx.accounts.collateral_vault_b,
                     &ctx.accounts.giving_mint,
                     ctx.accounts.giving_mint.decimals,
                 )
             } else {
                 (
-                    &ctx.accounts.fee_vault_a,
+                    &ctx.accounts.collateral_vault_a,
                     &ctx.accounts.giving_mint,
                     ctx.accounts.giving_mint.decimals,
                 )
@@ -147,16 +210,16 @@ pub fn repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
         let cpi_accounts = TransferChecked {
             from: collateral_vault.to_account_info(),
             to: ctx.accounts.borrower_collateral_ata.to_account_info(),
-            authority: ctx.accounts.liquidity_pool.to_account_info(),
+            authority: liquidity_pool.to_account_info(),
             mint: collateral_mint.to_account_info(),
         };
 
         let signer_seeds: &[&[&[u8]]] = &[
             &[
                 b"liquidity_pool",
-                ctx.accounts.liquidity_pool.mint_a.as_ref(),
-                ctx.accounts.liquidity_pool.mint_b.as_ref(),
-                ctx.accounts.liquidity_pool.authority.as_ref(),
+                liquidity_pool.mint_a.as_ref(),
+                liquidity_pool.mint_b.as_ref(),
+                liquidity_pool.authority.as_ref(),
                 &[ctx.bumps.liquidity_pool],
             ]
         ];
@@ -173,6 +236,7 @@ pub fn repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
         )?;
 
         borrow_info.total_collateral = 0;
+        borrow_info.is_closed = true;
     }
 
     Ok(())
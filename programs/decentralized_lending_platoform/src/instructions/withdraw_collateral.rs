@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer_checked, Mint, Token, TokenAccount, TransferChecked};
+
+use crate::{BorrowInfo, LiquidityPool};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct WithdrawCollateral<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+
+    #[account(address = borrower_account_info.collateral_mint @ ErrorCode::InvalidMint)]
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"borrower_account", liquidity_pool.key().as_ref(), borrower.key().as_ref(), &borrower_account_info.loan_id.to_le_bytes()],
+        bump,
+        has_one = borrower @ ErrorCode::InvalidBorrower,
+    )]
+    pub borrower_account_info: Account<'info, BorrowInfo>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"liquidity_pool",
+            liquidity_pool.mint_a.key().as_ref(),
+            liquidity_pool.mint_b.key().as_ref(),
+            liquidity_pool.authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub collateral_vault_b: Account<'info, TokenAccount>,
+
+    // Borrower's token account to receive the released collateral
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = borrower,
+    )]
+    pub borrower_collateral_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn process_withdraw_collateral(ctx: Context<WithdrawCollateral>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let liquidity_pool = &mut ctx.accounts.liquidity_pool;
+    let borrow_info = &mut ctx.accounts.borrower_account_info;
+    let collateral_mint = &ctx.accounts.collateral_mint;
+
+    require!(
+        amount > 0 && amount <= borrow_info.total_collateral,
+        ErrorCode::InvalidCollateralAmount
+    );
+    require!(
+        !borrow_info.is_closed && borrow_info.total_debt()? > 0,
+        ErrorCode::LoanNotActive
+    );
+
+    liquidity_pool.accrue_interest(clock.unix_timestamp)?;
+    let borrow_index = liquidity_pool.borrow_index(&borrow_info.borrow_mint);
+    borrow_info.accrue_interest(borrow_index)?;
+
+    borrow_info.total_collateral -= amount;
+
+    // The remaining collateral must still back the debt at the pool's loan-to-value ratio
+    let (borrow_price, collateral_price) = liquidity_pool.loan_prices(
+        &borrow_info.borrow_mint,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        &clock,
+    )?;
+    require!(
+        borrow_info.is_within_ltv(liquidity_pool.ltv_ratio, &borrow_price, &collateral_price)?,
+        ErrorCode::WithdrawalExceedsLtv
+    );
+    borrow_info.update_unhealthy_since(false, clock.unix_timestamp);
+
+    let collateral_vault = if borrow_info.collateral_mint == liquidity_pool.mint_a {
+        &ctx.accounts.collateral_vault_a
+    } else {
+        &ctx.accounts.collateral_vault_b
+    };
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        liquidity_pool.mint_a.as_ref(),
+        liquidity_pool.mint_b.as_ref(),
+        liquidity_pool.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    let cpi_accounts = TransferChecked {
+        from: collateral_vault.to_account_info(),
+        to: ctx.accounts.borrower_collateral_ata.to_account_info(),
+        authority: liquidity_pool.to_account_info(),
+        mint: collateral_mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer_seeds);
+    transfer_checked(cpi_ctx, amount, collateral_mint.decimals)?;
+
+    msg!("Withdrew {} collateral from loan {}, now {}", amount, borrow_info.loan_id, borrow_info.total_collateral);
+
+    Ok(())
+}
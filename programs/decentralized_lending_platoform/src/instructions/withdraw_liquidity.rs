@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, transfer_checked, Burn, TransferChecked};
+use anchor_spl::{token::Mint, token::TokenAccount, token::Token};
+use anchor_spl::associated_token::*;
+
+use crate::{cover_shortfall, LiquidityPool, LiquidityProviderInfo};
+
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct WithdrawLiquidity<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    pub token_mint_a: Account<'info, Mint>,
+
+    pub token_mint_b: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"lp_token_mint", liquidity_pool.key().as_ref()],
+        bump,
+        mint::authority = liquidity_pool,
+        mint::decimals = 6
+    )]
+    pub lp_token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_pool", token_mint_a.key().as_ref(), token_mint_b.key().as_ref(), liquidity_pool.authority.key().as_ref()],
+        bump
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity_provider", liquidity_pool.key().as_ref(), provider.key().as_ref()],
+        bump,
+        has_one = liquidity_pool @ ErrorCode::InvalidLiquidityPool
+    )]
+    pub liquidity_provider_account: Account<'info, LiquidityProviderInfo>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = provider
+    )]
+    pub provider_token_a_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = provider
+    )]
+    pub provider_token_b_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_a", token_mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint_a,
+        token::authority = liquidity_pool
+    )]
+    pub token_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_vault_b", token_mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = token_mint_b,
+        token::authority = liquidity_pool
+    )]
+    pub token_vault_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = lp_token_mint,
+        associated_token::authority = provider
+    )]
+    pub provider_lp_mint_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_a @ ErrorCode::InvalidOracle)]
+    pub oracle_a: UncheckedAccount<'info>,
+
+    /// CHECK: Pinned to the pool's configured oracle and parsed as a Pyth-format price account
+    #[account(address = liquidity_pool.oracle_b @ ErrorCode::InvalidOracle)]
+    pub oracle_b: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn process_withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_token_amount: u64) -> Result<()> {
+
+    let liquidity_pool_account = &mut ctx.accounts.liquidity_pool;
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+
+    // LP tokens can be transferred, so the burn below is what enforces the balance, not the provider's records
+    require!(lp_token_amount > 0, ErrorCode::InvalidLiquidityAmount);
+    require!(!liquidity_pool_account.has_flash_loan(), ErrorCode::FlashLoanActive);
+
+    liquidity_pool_account.accrue_interest(Clock::get()?.unix_timestamp)?;
+
+    // The provider owns a pro-rata share of everything the pool holds or has lent out, net of protocol reserves,
+    // though only what is currently available to lend can be paid out in each token
+    let token_a_amount = pro_rata_share(
+        liquidity_pool_account.lp_owned(&liquidity_pool_account.mint_a)? as u128,
+        lp_token_amount,
+        liquidity_pool_account.lp_supply,
+    )?;
+
+    let token_b_amount = pro_rata_share(
+        liquidity_pool_account.lp_owned(&liquidity_pool_account.mint_b)? as u128,
+        lp_token_amount,
+        liquidity_pool_account.lp_supply,
+    )?;
+
+    // When one side is lent out, the part of the share it cannot pay is paid in the other side at oracle prices
+    let available_a = liquidity_pool_account.available_a;
+    let available_b = liquidity_pool_account.available_b;
+    let (token_a_amount, token_b_amount) = if token_a_amount > available_a || token_b_amount > available_b {
+        let clock = Clock::get()?;
+        let (price_a, price_b) = liquidity_pool_account.load_prices(&ctx.accounts.oracle_a, &ctx.accounts.oracle_b, &clock)?;
+
+        if token_a_amount > available_a {
+            cover_shortfall(token_a_amount, available_a, &price_a, token_b_amount, &price_b)?
+        } else {
+            let (token_b_amount, token_a_amount) = cover_shortfall(token_b_amount, available_b, &price_b, token_a_amount, &price_a)?;
+            (token_a_amount, token_b_amount)
+        }
+    } else {
+        (token_a_amount, token_b_amount)
+    };
+
+    let mint_a = liquidity_pool_account.mint_a;
+    let mint_b = liquidity_pool_account.mint_b;
+    liquidity_pool_account.sub_available(&mint_a, token_a_amount)?;
+    liquidity_pool_account.sub_available(&mint_b, token_b_amount)?;
+
+    let burn_cpi = Burn {
+        mint: ctx.accounts.lp_token_mint.to_account_info(),
+        from: ctx.accounts.provider_lp_mint_ata.to_account_info(),
+        authority: ctx.accounts.provider.to_account_info(),
+    };
+
+    let burn_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), burn_cpi);
+
+    burn(burn_context, lp_token_amount)?;
+
+    let token_mint_a = ctx.accounts.token_mint_a.key();
+    let token_mint_b = ctx.accounts.token_mint_b.key();
+
+    let liquidity_pool_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        token_mint_a.as_ref(),
+        token_mint_b.as_ref(),
+        liquidity_pool_account.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    let cpi_accounts_1 = TransferChecked {
+        from: ctx.accounts.token_vault_a.to_account_info(),
+        to: ctx.accounts.provider_token_a_ata.to_account_info(),
+        authority: liquidity_pool_account.to_account_info(),
+        mint: ctx.accounts.token_mint_a.to_account_info(),
+    };
+
+    let cpi_context_1 = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts_1, liquidity_pool_seeds);
+
+    transfer_checked(cpi_context_1, token_a_amount, ctx.accounts.token_mint_a.decimals)?;
+
+    let cpi_accounts_2 = TransferChecked {
+        from: ctx.accounts.token_vault_b.to_account_info(),
+        to: ctx.accounts.provider_token_b_ata.to_account_info(),
+        authority: liquidity_pool_account.to_account_info(),
+        mint: ctx.accounts.token_mint_b.to_account_info(),
+    };
+
+    let cpi_context_2 = CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts_2, liquidity_pool_seeds);
+
+    transfer_checked(cpi_context_2, token_b_amount, ctx.accounts.token_mint_b.decimals)?;
+
+    // Deposits are tracked at cost, so they shrink by the withdrawn share of the pool rather than by the amounts paid out
+    let lp_supply = liquidity_pool_account.lp_supply;
+    liquidity_pool_account.total_deposited_a -= pro_rata_share(liquidity_pool_account.total_deposited_a as u128, lp_token_amount, lp_supply)?;
+    liquidity_pool_account.total_deposited_b -= pro_rata_share(liquidity_pool_account.total_deposited_b as u128, lp_token_amount, lp_supply)?;
+
+    liquidity_pool_account.lp_supply = liquidity_pool_account
+        .lp_supply
+        .checked_sub(lp_token_amount)
+        .ok_or(ErrorCode::Overflow)?;
+
+    // Reduce the provider's recorded deposits by the share of their position being withdrawn. Tokens received by
+    // transfer were never recorded, so only the part of the withdrawal covered by the records reduces them
+    let total_lp_tokens = liquidity_provider_account.total_lp_tokens;
+    let recorded_lp_tokens = lp_token_amount.min(total_lp_tokens);
+
+    if recorded_lp_tokens > 0 {
+        liquidity_provider_account.provided_token_a -= pro_rata_share(liquidity_provider_account.provided_token_a as u128, recorded_lp_tokens, total_lp_tokens)?;
+        liquidity_provider_account.provided_token_b -= pro_rata_share(liquidity_provider_account.provided_token_b as u128, recorded_lp_tokens, total_lp_tokens)?;
+        liquidity_provider_account.total_liquidity_provided -= pro_rata_share(liquidity_provider_account.total_liquidity_provided as u128, recorded_lp_tokens, total_lp_tokens)?;
+        liquidity_provider_account.total_lp_tokens -= recorded_lp_tokens;
+    }
+
+    msg!("Liquidity withdrawn successfully!");
+    msg!("Liquidity Pool Account: {:?}", liquidity_pool_account);
+    msg!("Liquidity Provider Account: {:?}", liquidity_provider_account);
+
+    Ok(())
+}
+
+fn pro_rata_share(total: u128, lp_token_amount: u64, lp_supply: u64) -> Result<u64> {
+    require!(lp_supply > 0, ErrorCode::InsufficientLiquidity);
+
+    let share = total
+        .checked_mul(lp_token_amount as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / lp_supply as u128;
+
+    Ok(u64::try_from(share).map_err(|_| ErrorCode::Overflow)?)
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, TransferChecked};
+use crate::state::LiquidityPool;
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(address = liquidity_pool.mint_a @ ErrorCode::InvalidMint)]
+    pub token_mint_a: Account<'info, Mint>,
+
+    #[account(address = liquidity_pool.mint_b @ ErrorCode::InvalidMint)]
+    pub token_mint_b: Account<'info, Mint>,
+
+    #[account(
+        seeds = [b"liquidity_pool", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.mint_b.key().as_ref(), authority.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::InvalidAuthority
+    )]
+    pub liquidity_pool: Account<'info, LiquidityPool>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_a", liquidity_pool.mint_a.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_a,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault_b", liquidity_pool.mint_b.key().as_ref(), liquidity_pool.key().as_ref()],
+        bump,
+        token::mint = liquidity_pool.mint_b,
+        token::authority = liquidity_pool,
+    )]
+    pub fee_vault_b: Account<'info, TokenAccount>,
+
+    #[account(mut, address = liquidity_pool.treasury_a @ ErrorCode::InvalidTreasury)]
+    pub treasury_a: Account<'info, TokenAccount>,
+
+    #[account(mut, address = liquidity_pool.treasury_b @ ErrorCode::InvalidTreasury)]
+    pub treasury_b: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct ProtocolFeesWithdrawn {
+    pub liquidity_pool: Pubkey,
+    pub treasury_a: Pubkey,
+    pub treasury_b: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub timestamp: i64,
+}
+
+pub fn process_withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount_a: u64, amount_b: u64) -> Result<()> {
+    let liquidity_pool = &ctx.accounts.liquidity_pool;
+
+    require!(
+        amount_a <= liquidity_pool.max_fee_withdrawal_a && amount_b <= liquidity_pool.max_fee_withdrawal_b,
+        ErrorCode::FeeWithdrawalLimitExceeded
+    );
+    require!(
+        amount_a <= ctx.accounts.fee_vault_a.amount && amount_b <= ctx.accounts.fee_vault_b.amount,
+        ErrorCode::InsufficientFees
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"liquidity_pool",
+        liquidity_pool.mint_a.as_ref(),
+        liquidity_pool.mint_b.as_ref(),
+        liquidity_pool.authority.as_ref(),
+        &[ctx.bumps.liquidity_pool],
+    ]];
+
+    if amount_a > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.fee_vault_a.to_account_info(),
+                to: ctx.accounts.treasury_a.to_account_info(),
+                authority: liquidity_pool.to_account_info(),
+                mint: ctx.accounts.token_mint_a.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        token::transfer_checked(cpi_ctx, amount_a, ctx.accounts.token_mint_a.decimals)?;
+    }
+
+    if amount_b > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.fee_vault_b.to_account_info(),
+                to: ctx.accounts.treasury_b.to_account_info(),
+                authority: liquidity_pool.to_account_info(),
+                mint: ctx.accounts.token_mint_b.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        token::transfer_checked(cpi_ctx, amount_b, ctx.accounts.token_mint_b.decimals)?;
+    }
+
+    emit!(ProtocolFeesWithdrawn {
+        liquidity_pool: liquidity_pool.key(),
+        treasury_a: liquidity_pool.treasury_a,
+        treasury_b: liquidity_pool.treasury_b,
+        amount_a,
+        amount_b,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Withdrew {} / {} protocol fees to the treasury", amount_a, amount_b);
+
+    Ok(())
+}
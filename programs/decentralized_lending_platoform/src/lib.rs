@@ -1,12 +1,18 @@
+// The #[program] expansion emits its IDL handlers as sibling items that call the deprecated `AccountInfo::realloc`,
+// so an allow on the program module does not reach them
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
 pub use instructions::*;
+pub use oracle::OracleConfig;
 pub use state::*;
 
 declare_id!("HpUXGMqxPUyT5msV9Wrm7dpGu6aNCivderuCVJeV3n11");
@@ -23,35 +29,89 @@ pub mod decentralized_lending_platoform {
         ctx: Context<InitializeLiquidityPool>, 
         ltv_ratio: u8, 
         liquidation_threshold: u8, 
-        liquidation_penalty: u8, 
-        interest_rate: u8
+        liquidation_bonus: LiquidationBonus, 
+        interest_rate_model: InterestRateModel
     ) -> Result<()> {
-        instructions::initialize_liquidity_pool(ctx, ltv_ratio, liquidation_threshold, liquidation_penalty, interest_rate)
+        instructions::process_initialize_liquidity_pool(ctx, ltv_ratio, liquidation_threshold, liquidation_bonus, interest_rate_model)
     }
 
     pub fn update_pool_parameters(
         ctx: Context<SetPoolParameters>,
         new_ltv_ratio: u8,
         new_liquidation_threshold: u8,
-        new_liquidation_penalty: u8,
-        new_interest_rate: u8
+        new_liquidation_bonus: LiquidationBonus,
+        new_interest_rate_model: InterestRateModel,
+        new_close_factor: u8,
+        new_loan_terms: LoanTerms
     ) -> Result<()> {
-        instructions::set_pool_parameters(ctx, new_ltv_ratio, new_liquidation_threshold, new_liquidation_penalty, new_interest_rate)
+        instructions::set_pool_parameters(ctx, new_ltv_ratio, new_liquidation_threshold, new_liquidation_bonus, new_interest_rate_model, new_close_factor, new_loan_terms)
+    }
+
+    pub fn update_pool_oracles(ctx: Context<SetPoolOracles>, oracle_config: OracleConfig) -> Result<()> {
+        instructions::set_pool_oracles(ctx, oracle_config)
+    }
+
+    pub fn update_pool_fees(ctx: Context<SetPoolParameters>, fee_config: FeeConfig) -> Result<()> {
+        instructions::set_pool_fees(ctx, fee_config)
+    }
+
+    pub fn update_pool_treasury(ctx: Context<SetPoolTreasury>, max_fee_withdrawal_a: u64, max_fee_withdrawal_b: u64) -> Result<()> {
+        instructions::set_pool_treasury(ctx, max_fee_withdrawal_a, max_fee_withdrawal_b)
     }
 
     pub fn initialize_liquidity_provider(ctx: Context<InitLiquidityProvider>) -> Result<()> {
-        instructions::initialize_liquidity_provider(ctx)
+        instructions::process_initialize_liquidity_provider(ctx)
     }
 
     pub fn provide_liquidity(ctx: Context<ProvideLiquidity>, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
-        instructions::provide_liquidity(ctx, token_a_amount, token_b_amount)
+        instructions::process_provide_liquidity(ctx, token_a_amount, token_b_amount)
     }
 
-    pub fn borrow_funds(ctx: Context<BorrowFunds>, amount: u64, borrow_duration: u8) -> Result<()> {
-        instructions::borrow_funds(ctx, amount, borrow_duration)
+    pub fn withdraw_liquidity(ctx: Context<WithdrawLiquidity>, lp_token_amount: u64) -> Result<()> {
+        instructions::process_withdraw_liquidity(ctx, lp_token_amount)
+    }
+
+    pub fn borrow_funds(ctx: Context<BorrowFunds>, loan_id: u64, amount: u64, borrow_duration: u8) -> Result<()> {
+        instructions::process_borrow_funds(ctx, loan_id, amount, borrow_duration)
+    }
+
+    pub fn borrow_more(ctx: Context<BorrowMore>, borrow_amount: u64) -> Result<()> {
+        instructions::process_borrow_more(ctx, borrow_amount)
     }
 
     pub fn repay_funds(ctx: Context<RepayFunds>, repay_amount: u64) -> Result<()> {
-        instructions::repay_funds(ctx, repay_amount)
+        instructions::process_repay_funds(ctx, repay_amount)
+    }
+
+    pub fn extend_loan(ctx: Context<ExtendLoan>, borrow_duration: u8) -> Result<()> {
+        instructions::process_extend_loan(ctx, borrow_duration)
+    }
+
+    pub fn add_collateral(ctx: Context<AddCollateral>, amount: u64) -> Result<()> {
+        instructions::process_add_collateral(ctx, amount)
+    }
+
+    pub fn withdraw_collateral(ctx: Context<WithdrawCollateral>, amount: u64) -> Result<()> {
+        instructions::process_withdraw_collateral(ctx, amount)
+    }
+
+    pub fn liquidate(ctx: Context<Liquidate>, repay_amount: u64) -> Result<()> {
+        instructions::process_liquidate(ctx, repay_amount)
+    }
+
+    pub fn flag_unhealthy_loan(ctx: Context<FlagUnhealthyLoan>) -> Result<()> {
+        instructions::process_flag_unhealthy_loan(ctx)
+    }
+
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount_a: u64, amount_b: u64) -> Result<()> {
+        instructions::process_withdraw_protocol_fees(ctx, amount_a, amount_b)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+        instructions::process_flash_borrow(ctx, amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashRepay>, amount: u64) -> Result<()> {
+        instructions::process_flash_repay(ctx, amount)
     }
 }
@@ -0,0 +1,304 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{PYTH_PROGRAM_ID, VALUE_DECIMALS};
+use crate::error::ErrorCode;
+
+// Layout of a Pyth (v2) price account. Only the header, EMA and aggregate
+// sections are read. Price accounts must be owned by the Pyth program, which
+// the mock price accounts loaded by the local test validator are as well.
+const MAGIC: u32 = 0xa1b2_c3d4;
+const VERSION_2: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const EMA_PRICE_OFFSET: usize = 48;
+const EMA_CONF_OFFSET: usize = 72;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// Per-pool limits a price must satisfy before it is used. A limit of zero disables that check.
+#[derive(Clone, Copy, Debug, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct OracleConfig {
+    pub max_price_age_slots: u64, //Slots since the price was last published
+    pub max_price_age_seconds: u32, //Seconds since the price was last published
+    pub max_confidence_bps: u16, //Confidence interval relative to the price
+    pub max_ema_deviation_bps: u16, //Spot price deviation from the EMA price
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+    pub publish_time: i64,
+    pub publish_slot: u64,
+    pub mint_decimals: u8, //Decimals of the mint being priced, so base-unit amounts can be valued
+}
+
+impl OraclePrice {
+    pub fn load(price_account: &AccountInfo) -> Result<Self> {
+        // Any program can write the magic bytes, so only trust accounts written by Pyth
+        require_keys_eq!(*price_account.owner, PYTH_PROGRAM_ID, ErrorCode::InvalidOracle);
+
+        let data = price_account.try_borrow_data()?;
+
+        require!(data.len() >= PRICE_ACCOUNT_MIN_LEN, ErrorCode::InvalidOracle);
+        require!(
+            read_u32(&data, MAGIC_OFFSET) == MAGIC
+                && read_u32(&data, VERSION_OFFSET) == VERSION_2
+                && read_u32(&data, ACCOUNT_TYPE_OFFSET) == ACCOUNT_TYPE_PRICE,
+            ErrorCode::InvalidOracle
+        );
+        require!(read_u32(&data, AGG_STATUS_OFFSET) == STATUS_TRADING, ErrorCode::InvalidOraclePrice);
+
+        let price = Self {
+            price: read_i64(&data, AGG_PRICE_OFFSET),
+            conf: read_u64(&data, AGG_CONF_OFFSET),
+            expo: read_u32(&data, EXPO_OFFSET) as i32,
+            ema_price: read_i64(&data, EMA_PRICE_OFFSET),
+            ema_conf: read_u64(&data, EMA_CONF_OFFSET),
+            publish_time: read_i64(&data, TIMESTAMP_OFFSET),
+            publish_slot: read_u64(&data, AGG_PUB_SLOT_OFFSET),
+            mint_decimals: 0,
+        };
+
+        require!(price.price > 0, ErrorCode::InvalidOraclePrice);
+
+        Ok(price)
+    }
+
+    /// Loads the price of a mint with `mint_decimals` and rejects it unless it is fresh, precise and close to its EMA per `config`.
+    pub fn load_checked(price_account: &AccountInfo, mint_decimals: u8, config: &OracleConfig, clock: &Clock) -> Result<Self> {
+        let mut price = Self::load(price_account)?;
+        price.mint_decimals = mint_decimals;
+        price.check(config, clock)?;
+
+        Ok(price)
+    }
+
+    /// Rejects the price unless it passes every guard enabled in `config` at `clock`.
+    pub fn check(&self, config: &OracleConfig, clock: &Clock) -> Result<()> {
+        if config.max_price_age_slots > 0 {
+            require!(
+                clock.slot.saturating_sub(self.publish_slot) <= config.max_price_age_slots,
+                ErrorCode::StaleOraclePrice
+            );
+        }
+
+        if config.max_price_age_seconds > 0 {
+            require!(
+                clock.unix_timestamp.saturating_sub(self.publish_time) <= config.max_price_age_seconds as i64,
+                ErrorCode::StaleOraclePrice
+            );
+        }
+
+        if config.max_confidence_bps > 0 {
+            require!(
+                self.conf as u128 * 10_000 <= self.price as u128 * config.max_confidence_bps as u128,
+                ErrorCode::OracleConfidenceTooWide
+            );
+        }
+
+        if config.max_ema_deviation_bps > 0 {
+            require!(self.ema_price > 0, ErrorCode::InvalidOraclePrice);
+
+            let deviation = (self.price as i128 - self.ema_price as i128).unsigned_abs();
+            require!(
+                deviation * 10_000 <= self.ema_price as u128 * config.max_ema_deviation_bps as u128,
+                ErrorCode::OraclePriceDeviationTooLarge
+            );
+        }
+
+        Ok(())
+    }
+
+    /// USD value of `amount` base units, scaled by 10^VALUE_DECIMALS.
+    pub fn value_of(&self, amount: u64) -> Result<u128> {
+        let value = (amount as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        scale(value, self.value_exponent())
+    }
+
+    /// Number of base units worth `value` (scaled by 10^VALUE_DECIMALS), rounded down.
+    pub fn amount_for(&self, value: u128) -> Result<u64> {
+        let amount = scale(value, -self.value_exponent())? / self.price as u128;
+
+        Ok(u64::try_from(amount).map_err(|_| ErrorCode::MathOverflow)?)
+    }
+
+    /// Power of ten taking `base units * price` to a USD value with VALUE_DECIMALS decimals.
+    fn value_exponent(&self) -> i32 {
+        self.expo + VALUE_DECIMALS as i32 - self.mint_decimals as i32
+    }
+}
+
+/// Whether `err` rejects the price itself rather than the oracle account, so an instruction that only improves
+/// a position can go ahead without it.
+pub fn is_unusable_price(err: &Error) -> bool {
+    [
+        ErrorCode::InvalidOraclePrice,
+        ErrorCode::StaleOraclePrice,
+        ErrorCode::OracleConfidenceTooWide,
+        ErrorCode::OraclePriceDeviationTooLarge,
+    ]
+    .into_iter()
+    .any(|code| *err == code.into())
+}
+
+/// Multiplies `value` by 10^exponent, truncating when the exponent is negative.
+fn scale(value: u128, exponent: i32) -> Result<u128> {
+    let factor = 10u128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    if exponent >= 0 {
+        Ok(value.checked_mul(factor).ok_or(ErrorCode::MathOverflow)?)
+    } else {
+        Ok(value / factor)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // $1.00 with 8 decimals, published at slot 1_000 and time 10_000
+    const PRICE: OraclePrice = OraclePrice {
+        price: 100_000_000,
+        conf: 50_000,
+        expo: -8,
+        ema_price: 100_000_000,
+        ema_conf: 50_000,
+        publish_time: 10_000,
+        publish_slot: 1_000,
+        mint_decimals: 6,
+    };
+
+    const CONFIG: OracleConfig = OracleConfig {
+        max_price_age_slots: 25,
+        max_price_age_seconds: 60,
+        max_confidence_bps: 100,
+        max_ema_deviation_bps: 500,
+    };
+
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock { slot, unix_timestamp, ..Clock::default() }
+    }
+
+    #[test]
+    fn stale_price_by_slots() {
+        assert!(PRICE.check(&CONFIG, &clock(1_025, 10_000)).is_ok());
+        assert_eq!(PRICE.check(&CONFIG, &clock(1_026, 10_000)).unwrap_err(), ErrorCode::StaleOraclePrice.into());
+
+        let disabled = OracleConfig { max_price_age_slots: 0, ..CONFIG };
+        assert!(PRICE.check(&disabled, &clock(u64::MAX, 10_000)).is_ok());
+    }
+
+    #[test]
+    fn stale_price_by_seconds() {
+        assert!(PRICE.check(&CONFIG, &clock(1_000, 10_060)).is_ok());
+        assert_eq!(PRICE.check(&CONFIG, &clock(1_000, 10_061)).unwrap_err(), ErrorCode::StaleOraclePrice.into());
+
+        let disabled = OracleConfig { max_price_age_seconds: 0, ..CONFIG };
+        assert!(PRICE.check(&disabled, &clock(1_000, i64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn confidence_too_wide() {
+        // 100 bps of $1.00 is 1_000_000
+        let at_limit = OraclePrice { conf: 1_000_000, ..PRICE };
+        assert!(at_limit.check(&CONFIG, &clock(1_000, 10_000)).is_ok());
+
+        let too_wide = OraclePrice { conf: 1_000_001, ..PRICE };
+        assert_eq!(too_wide.check(&CONFIG, &clock(1_000, 10_000)).unwrap_err(), ErrorCode::OracleConfidenceTooWide.into());
+    }
+
+    #[test]
+    fn price_deviates_from_ema() {
+        // 500 bps of the $1.00 EMA either way
+        let at_limit = OraclePrice { price: 105_000_000, ..PRICE };
+        assert!(at_limit.check(&CONFIG, &clock(1_000, 10_000)).is_ok());
+
+        let below_limit = OraclePrice { price: 95_000_000, ..PRICE };
+        assert!(below_limit.check(&CONFIG, &clock(1_000, 10_000)).is_ok());
+
+        let too_high = OraclePrice { price: 105_000_001, ..PRICE };
+        assert_eq!(too_high.check(&CONFIG, &clock(1_000, 10_000)).unwrap_err(), ErrorCode::OraclePriceDeviationTooLarge.into());
+
+        let too_low = OraclePrice { price: 94_999_999, ..PRICE };
+        assert_eq!(too_low.check(&CONFIG, &clock(1_000, 10_000)).unwrap_err(), ErrorCode::OraclePriceDeviationTooLarge.into());
+    }
+
+    // $2.50 per token with 6 decimals and $0.0312 per token with 9 decimals
+    const TOKEN_A: OraclePrice = OraclePrice { price: 250_000_000, ..PRICE };
+    const TOKEN_B: OraclePrice = OraclePrice { price: 3_120_000, mint_decimals: 9, ..PRICE };
+
+    #[test]
+    fn value_across_decimals() {
+        assert_eq!(TOKEN_A.value_of(1_000_000).unwrap(), 2_500_000);
+        assert_eq!(TOKEN_B.value_of(1_000_000_000).unwrap(), 31_200);
+
+        // One token of A buys 80.128205128 of B, rounded down
+        let value = TOKEN_A.value_of(1_000_000).unwrap();
+        assert_eq!(TOKEN_A.amount_for(value).unwrap(), 1_000_000);
+        assert_eq!(TOKEN_B.amount_for(value).unwrap(), 80_128_205_128);
+    }
+
+    #[test]
+    fn value_round_trip_rounds_down() {
+        // Valuing truncates to a millionth of a dollar, so converting back loses less than that much of the token
+        let amount = 123_456_789;
+        let value = TOKEN_B.value_of(amount).unwrap();
+        assert_eq!(value, 3_851);
+
+        let back = TOKEN_B.amount_for(value).unwrap();
+        assert_eq!(back, 123_429_487);
+        assert!(amount - back <= TOKEN_B.amount_for(1).unwrap());
+
+        // Token A has as many decimals as values do, so at most one base unit is lost
+        let back = TOKEN_A.amount_for(TOKEN_A.value_of(amount).unwrap()).unwrap();
+        assert_eq!(back, amount - 1);
+        assert_eq!(TOKEN_A.amount_for(TOKEN_A.value_of(2_000_000).unwrap()).unwrap(), 2_000_000);
+    }
+
+    #[test]
+    fn only_price_errors_are_unusable_prices() {
+        let stale = PRICE.check(&CONFIG, &clock(2_000, 10_000)).unwrap_err();
+        assert!(is_unusable_price(&stale));
+        assert!(!is_unusable_price(&ErrorCode::InvalidOracle.into()));
+        assert!(!is_unusable_price(&ErrorCode::MathOverflow.into()));
+    }
+
+    #[test]
+    fn scale_by_powers_of_ten() {
+        assert_eq!(scale(123_456, -3).unwrap(), 123);
+        assert_eq!(scale(123, 0).unwrap(), 123);
+        assert_eq!(scale(123, 3).unwrap(), 123_000);
+        assert!(scale(u128::MAX, 1).is_err());
+        assert!(scale(1, 39).is_err());
+    }
+}
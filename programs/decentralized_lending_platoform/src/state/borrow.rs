@@ -1,22 +1,284 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{SECONDS_PER_DAY, SECONDS_PER_YEAR};
+use crate::error::ErrorCode;
+use crate::oracle::OraclePrice;
+
 #[account]
 #[derive(Debug, InitSpace)]
 pub struct BorrowInfo {
     pub borrower: Pubkey,
     pub borrowed_from_pool: Pubkey,
-    pub total_borrowed: u64,
+    pub loan_id: u64, // distinguishes a borrower's concurrent loans within a pool
+    pub borrow_mint: Pubkey, // mint lent out of the pool
+    pub collateral_mint: Pubkey, // mint posted as collateral, held by the pool
+    pub total_borrowed: u64, // outstanding principal
+    pub origination_fee: u64, // part of the principal withheld as a fee when the loan was opened
+    pub accrued_interest: u64, // interest owed on top of the principal
+    pub borrow_index: u128, // pool borrow index the debt was last brought up to date with
     pub total_collateral: u64,
     pub borrowed_at: i64,
+    pub unhealthy_since: i64, // when the position was first seen past the liquidation threshold, 0 while healthy
     pub borrow_duration: BorrowDuration,
+    pub expires_at: i64, // end of the loan term, borrowed_at plus the borrow duration
+    pub term_principal: u64, // principal drawn over the current term, before any repayment
+    pub term_start_index: u128, // pool borrow index when the current term started
+    pub term_interest_paid: u64, // interest repaid during the current term
     pub repaid_amount: u64,
     pub is_closed: bool, // mark when loan is fully repaid
 }
 
+impl BorrowInfo {
+    pub fn total_debt(&self) -> Result<u64> {
+        let debt = self.total_borrowed
+            .checked_add(self.accrued_interest)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(debt)
+    }
+
+    /// Brings the debt up to date with the pool's current borrow index for the borrowed mint.
+    pub fn accrue_interest(&mut self, current_index: u128) -> Result<()> {
+        if self.borrow_index != 0 && current_index > self.borrow_index {
+            let debt = (self.total_debt()? as u128)
+                .checked_mul(current_index)
+                .ok_or(ErrorCode::MathOverflow)?
+                / self.borrow_index;
+            let debt = u64::try_from(debt).map_err(|_| ErrorCode::MathOverflow)?;
+
+            self.accrued_interest = debt
+                .checked_sub(self.total_borrowed)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        self.borrow_index = current_index;
+
+        Ok(())
+    }
+
+    /// Starts a new term with the outstanding principal, as of the pool's `current_index`.
+    pub fn start_term(&mut self, now: i64, current_index: u128) {
+        self.borrowed_at = now;
+        self.term_principal = self.total_borrowed;
+        self.term_start_index = current_index;
+        self.term_interest_paid = 0;
+    }
+
+    /// Interest to add on top of the accrued interest so the term pays at least `min_interest_period` seconds of interest
+    /// on its original principal, at the rate the loan has accrued at so far. Only owed by the repayment that closes the
+    /// loan; `rate_bps` stands in for the accrued rate when the loan is closed in the same second it was opened.
+    pub fn minimum_interest_top_up(&self, now: i64, min_interest_period: u32, current_index: u128, rate_bps: u64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.borrowed_at);
+        if elapsed >= min_interest_period as i64 {
+            return Ok(0);
+        }
+
+        let minimum_interest = if elapsed > 0 && self.term_start_index > 0 {
+            // Interest the original principal would have accrued so far, extrapolated to the whole period. Everything is
+            // multiplied out before dividing, since the accrued part alone is usually a fraction of a base unit
+            let accrued = (self.term_principal as u128)
+                .checked_mul(current_index.saturating_sub(self.term_start_index))
+                .ok_or(ErrorCode::MathOverflow)?;
+            let denominator = self.term_start_index
+                .checked_mul(elapsed as u128)
+                .ok_or(ErrorCode::MathOverflow)?;
+
+            match accrued.checked_mul(min_interest_period as u128) {
+                Some(numerator) => numerator / denominator,
+                // Only very large loans get here, where dividing first loses a negligible fraction of a unit
+                None => accrued / self.term_start_index * min_interest_period as u128 / elapsed as u128,
+            }
+        } else {
+            (self.term_principal as u128)
+                .checked_mul(rate_bps as u128 * min_interest_period as u128)
+                .ok_or(ErrorCode::MathOverflow)?
+                / (10_000 * SECONDS_PER_YEAR as u128)
+        };
+        let minimum_interest = u64::try_from(minimum_interest).map_err(|_| ErrorCode::MathOverflow)?;
+
+        let charged = self.term_interest_paid
+            .checked_add(self.accrued_interest)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(minimum_interest.saturating_sub(charged))
+    }
+
+    /// Applies a repayment to the accrued interest first and the principal after, returning the interest portion.
+    pub fn apply_repayment(&mut self, amount: u64) -> Result<u64> {
+        let interest_paid = amount.min(self.accrued_interest);
+        let principal_paid = amount - interest_paid;
+
+        self.accrued_interest -= interest_paid;
+        self.term_interest_paid = self.term_interest_paid
+            .checked_add(interest_paid)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.total_borrowed = self.total_borrowed
+            .checked_sub(principal_paid)
+            .ok_or(ErrorCode::InvalidRepayAmount)?;
+        self.repaid_amount = self.repaid_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(interest_paid)
+    }
+
+    /// Starts the liquidation auction clock the first time the position is seen unhealthy, and resets it once healthy again.
+    pub fn update_unhealthy_since(&mut self, is_unhealthy: bool, now: i64) {
+        if !is_unhealthy {
+            self.unhealthy_since = 0;
+        } else if self.unhealthy_since == 0 {
+            self.unhealthy_since = now;
+        }
+    }
+
+    /// A position is unhealthy once its debt value exceeds `liquidation_threshold` percent of its collateral value.
+    pub fn is_unhealthy(&self, liquidation_threshold: u8, borrow_price: &OraclePrice, collateral_price: &OraclePrice) -> Result<bool> {
+        Ok(borrow_price.value_of(self.total_debt()?)? > self.max_debt_value(liquidation_threshold, collateral_price)?)
+    }
+
+    /// Whether the debt value is within `ltv_ratio` percent of the collateral value, as required to release collateral.
+    pub fn is_within_ltv(&self, ltv_ratio: u8, borrow_price: &OraclePrice, collateral_price: &OraclePrice) -> Result<bool> {
+        Ok(borrow_price.value_of(self.total_debt()?)? <= self.max_debt_value(ltv_ratio, collateral_price)?)
+    }
+
+    /// How much more of the borrowed mint the position can take on before reaching `ltv_ratio`.
+    pub fn borrow_capacity(&self, ltv_ratio: u8, borrow_price: &OraclePrice, collateral_price: &OraclePrice) -> Result<u64> {
+        let remaining_value = self
+            .max_debt_value(ltv_ratio, collateral_price)?
+            .saturating_sub(borrow_price.value_of(self.total_debt()?)?);
+
+        borrow_price.amount_for(remaining_value)
+    }
+
+    fn max_debt_value(&self, percent: u8, collateral_price: &OraclePrice) -> Result<u128> {
+        let value = collateral_price
+            .value_of(self.total_collateral)?
+            .checked_mul(percent as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            / 100;
+
+        Ok(value)
+    }
+}
+
 #[derive(Clone, Debug, Copy, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
-#[repr(u8)] 
+#[repr(u8)]
 pub enum BorrowDuration {
     TenDays = 10,
     TwentyDays = 20,
     ThirtyDays = 30
-}
\ No newline at end of file
+}
+
+impl BorrowDuration {
+    /// Maps the instruction argument (0, 1, 2) to a duration.
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(BorrowDuration::TenDays),
+            1 => Ok(BorrowDuration::TwentyDays),
+            2 => Ok(BorrowDuration::ThirtyDays),
+            _ => Err(ErrorCode::InvalidDuration.into()),
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        *self as i64 * SECONDS_PER_DAY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::INDEX_SCALE;
+
+    const DAY: i64 = SECONDS_PER_DAY;
+
+    fn loan(principal: u64) -> BorrowInfo {
+        let mut loan = BorrowInfo {
+            borrower: Pubkey::default(),
+            borrowed_from_pool: Pubkey::default(),
+            loan_id: 0,
+            borrow_mint: Pubkey::default(),
+            collateral_mint: Pubkey::default(),
+            total_borrowed: principal,
+            origination_fee: 0,
+            accrued_interest: 0,
+            borrow_index: INDEX_SCALE,
+            total_collateral: 0,
+            borrowed_at: 0,
+            unhealthy_since: 0,
+            borrow_duration: BorrowDuration::TenDays,
+            expires_at: 10 * DAY,
+            term_principal: 0,
+            term_start_index: 0,
+            term_interest_paid: 0,
+            repaid_amount: 0,
+            is_closed: false,
+        };
+        loan.start_term(0, INDEX_SCALE);
+        loan
+    }
+
+    #[test]
+    fn partial_then_full_repay_pays_the_minimum_once() {
+        let mut loan = loan(1_000_000);
+
+        // The index grows 0.1% over the first hour
+        let index = INDEX_SCALE * 1_001 / 1_000;
+        loan.accrue_interest(index).unwrap();
+        assert_eq!(loan.accrued_interest, 1_000);
+
+        // A partial repay is not topped up
+        let interest_paid = loan.apply_repayment(500_000).unwrap();
+        assert_eq!(interest_paid, 1_000);
+        assert_eq!(loan.accrued_interest, 0);
+        assert_eq!(loan.total_borrowed, 501_000);
+
+        // Closing the loan an hour in pays a full day at the accrued rate on the original principal,
+        // less the interest already paid
+        let top_up = loan.minimum_interest_top_up(DAY / 24, DAY as u32, index, 0).unwrap();
+        assert_eq!(top_up, 23_000);
+
+        loan.accrued_interest += top_up;
+        assert_eq!(loan.total_debt().unwrap(), 524_000);
+
+        loan.apply_repayment(loan.total_debt().unwrap()).unwrap();
+        assert_eq!(loan.total_debt().unwrap(), 0);
+        assert_eq!(loan.term_interest_paid, 24_000);
+    }
+
+    #[test]
+    fn minimum_interest_ends_with_the_period() {
+        let mut loan = loan(1_000_000);
+
+        let index = INDEX_SCALE * 11 / 10;
+        loan.accrue_interest(index).unwrap();
+
+        assert_eq!(loan.minimum_interest_top_up(DAY, DAY as u32, index, 1_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn minimum_interest_at_a_realistic_rate() {
+        // 5% APR for one minute, the index growth a real pool would see
+        let index = INDEX_SCALE + INDEX_SCALE * 500 * 60 / (10_000 * SECONDS_PER_YEAR as u128);
+
+        // 1 USDC accrues under one base unit in a minute, but a day's worth is about 137 units
+        let mut small = loan(1_000_000);
+        small.accrue_interest(index).unwrap();
+        assert_eq!(small.accrued_interest, 0);
+        assert_eq!(small.minimum_interest_top_up(60, DAY as u32, index, 0).unwrap(), 136);
+
+        let mut large = loan(1_000_000_000);
+        large.accrue_interest(index).unwrap();
+        assert_eq!(large.accrued_interest, 95);
+        assert_eq!(large.minimum_interest_top_up(60, DAY as u32, index, 0).unwrap(), 136_986 - 95);
+    }
+
+    #[test]
+    fn same_second_close_uses_the_pool_rate() {
+        let loan = loan(1_000_000);
+
+        // No time has passed, so a year-long minimum period costs a year at the 10% pool rate
+        let top_up = loan.minimum_interest_top_up(0, SECONDS_PER_YEAR as u32, INDEX_SCALE, 1_000).unwrap();
+        assert_eq!(top_up, 100_000);
+    }
+}
@@ -1,24 +1,54 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::SECONDS_PER_YEAR;
+use crate::error::ErrorCode;
+use crate::oracle::{OracleConfig, OraclePrice};
+
 #[account]
 #[derive(Debug, InitSpace)]
 pub struct LiquidityPool {
     pub authority: Pubkey, //Who controls the pool
     pub mint_a: Pubkey, //Liquidity pool token 1
     pub mint_b: Pubkey, //Liquidity pool token 2
+    pub decimals_a: u8, //Decimals of mint_a, used to normalize valuations
+    pub decimals_b: u8, //Decimals of mint_b, used to normalize valuations
     pub lp_mint: Pubkey, //Lp token mint
     pub vault_a: Pubkey, //Vault to hold mint_a tokens
     pub vault_b: Pubkey, //Vault to hold mint_b tokens
     pub fees_vault_a: Pubkey,
     pub fees_vault_b: Pubkey,
-    pub total_liquidity: u64, //Whats the total liquidity provided 
+    pub collateral_vault_a: Pubkey, //Vault holding mint_a posted as collateral, kept apart from lendable liquidity
+    pub collateral_vault_b: Pubkey, //Vault holding mint_b posted as collateral, kept apart from lendable liquidity
+    pub treasury_a: Pubkey, //mint_a token account protocol fees are swept to, unset until configured
+    pub treasury_b: Pubkey, //mint_b token account protocol fees are swept to, unset until configured
+    pub max_fee_withdrawal_a: u64, //Most mint_a a single protocol fee withdrawal can move
+    pub max_fee_withdrawal_b: u64, //Most mint_b a single protocol fee withdrawal can move
+    pub oracle_a: Pubkey, //Pyth-format price account for mint_a
+    pub oracle_b: Pubkey, //Pyth-format price account for mint_b
+    pub oracle_config: OracleConfig, //Staleness, confidence and deviation limits applied to oracle prices
+    pub total_deposited_a: u64, //mint_a deposited by LPs, net of withdrawals
+    pub total_deposited_b: u64, //mint_b deposited by LPs, net of withdrawals
+    pub available_a: u64, //mint_a in vault_a that is free to lend or withdraw
+    pub available_b: u64, //mint_b in vault_b that is free to lend or withdraw
     pub total_borrowed_a: u64, //Whats the total amount of liquidity being borrowed for mint_a in USDC
     pub total_borrowed_b: u64, //Whats the total amount of liquidity being borrowed for mint_b in USDC
     pub total_borrowed: u64, //Whats the total borrowed total_borrowed_a + total_borrowed_b in USDC
+    pub reserves_owed_a: u64, //Reserve factor's share of accrued mint_a interest, still owed by borrowers
+    pub reserves_owed_b: u64, //Reserve factor's share of accrued mint_b interest, still owed by borrowers
+    pub reserves_collected_a: u64, //Total mint_a reserves routed into fee_vault_a as interest was repaid
+    pub reserves_collected_b: u64, //Total mint_b reserves routed into fee_vault_b as interest was repaid
     pub ltv_ratio: u8, //Loan to value ration 0 - 100
     pub liquidation_threshold: u8, //At what percentage the collateral should be liquidated. 0 - 100
-    pub liquidation_penalty: u8, //Penalty applied when liquidating a position. The liquidator receives this bonus, incentivizing them them to perfrom the action
-    pub interest_rate: u8, //Annualized interest rate applied to borrowed tokens.
+    pub loan_terms: LoanTerms, //Timing rules applied to fixed-term loans
+    pub close_factor: u8, //Max percentage of a position's debt a single liquidation can repay. 1 - 100
+    pub liquidation_bonus: LiquidationBonus, //Dutch-auction discount on seized collateral, incentivizing liquidators to perform the action
+    pub interest_rate_model: InterestRateModel, //Utilization-driven annualized rate curve applied to borrowed tokens.
+    pub fee_config: FeeConfig, //Fees charged by the pool
+    pub flash_borrowed_a: u64, //mint_a currently out on a flash loan, repaid within the same transaction
+    pub flash_borrowed_b: u64, //mint_b currently out on a flash loan, repaid within the same transaction
+    pub borrow_index_a: u128, //Cumulative borrow index for mint_a debt, scaled by INDEX_SCALE
+    pub borrow_index_b: u128, //Cumulative borrow index for mint_b debt, scaled by INDEX_SCALE
+    pub last_accrual_timestamp: i64, //Unix timestamp interest was last accrued at
     pub created_at: i64, //Unix timestamp of when the pool was initialized.
     pub lp_supply: u64, //Tracks total LP tokens minted
     pub bump: u8, //Stores the liquidity_pool account bump 
@@ -26,4 +56,481 @@ pub struct LiquidityPool {
     pub vault_b_bump: u8, //Stores the vault_b account bump
     pub fees_vault_a_bump: u8,
     pub fees_vault_b_bump: u8,
-}
\ No newline at end of file
+    pub collateral_vault_a_bump: u8,
+    pub collateral_vault_b_bump: u8,
+}
+
+impl LiquidityPool {
+    /// Grows both borrow indices, and the outstanding debt they track, by the interest owed since the last accrual.
+    pub fn accrue_interest(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_accrual_timestamp);
+        if elapsed <= 0 {
+            return Ok(());
+        }
+
+        let rate_a = self.borrow_rate_bps(&self.mint_a);
+        let rate_b = self.borrow_rate_bps(&self.mint_b);
+
+        let (borrow_index_a, total_borrowed_a) = accrue_side(self.borrow_index_a, self.total_borrowed_a, rate_a, elapsed as u64)?;
+        let (borrow_index_b, total_borrowed_b) = accrue_side(self.borrow_index_b, self.total_borrowed_b, rate_b, elapsed as u64)?;
+
+        // The reserve factor's share of the new interest belongs to the protocol rather than the LPs
+        let reserve_factor_bps = self.fee_config.reserve_factor_bps as u128;
+        let reserves_a = (total_borrowed_a - self.total_borrowed_a) as u128 * reserve_factor_bps / 10_000;
+        let reserves_b = (total_borrowed_b - self.total_borrowed_b) as u128 * reserve_factor_bps / 10_000;
+
+        self.reserves_owed_a = self.reserves_owed_a
+            .checked_add(reserves_a as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.reserves_owed_b = self.reserves_owed_b
+            .checked_add(reserves_b as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.borrow_index_a = borrow_index_a;
+        self.borrow_index_b = borrow_index_b;
+        self.total_borrowed_a = total_borrowed_a;
+        self.total_borrowed_b = total_borrowed_b;
+        self.total_borrowed = total_borrowed_a
+            .checked_add(total_borrowed_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.last_accrual_timestamp = now;
+
+        Ok(())
+    }
+
+    /// Tokens of one side owned by the LPs: the available balance plus outstanding debt, less reserves owed to the protocol.
+    pub fn lp_owned(&self, mint: &Pubkey) -> Result<u64> {
+        let (available, total_borrowed, reserves_owed) = if *mint == self.mint_a {
+            (self.available_a, self.total_borrowed_a, self.reserves_owed_a)
+        } else {
+            (self.available_b, self.total_borrowed_b, self.reserves_owed_b)
+        };
+
+        let owned = available
+            .checked_add(total_borrowed)
+            .ok_or(ErrorCode::MathOverflow)?
+            .saturating_sub(reserves_owed);
+
+        Ok(owned)
+    }
+
+    /// USD value of everything the LPs own across both sides.
+    pub fn total_value(&self, price_a: &OraclePrice, price_b: &OraclePrice) -> Result<u128> {
+        let value = price_a
+            .value_of(self.lp_owned(&self.mint_a)?)?
+            .checked_add(price_b.value_of(self.lp_owned(&self.mint_b)?)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(value)
+    }
+
+    /// Settles the reserve factor's share of `interest_paid` on a repayment, returning the amount to route into the fee vault.
+    pub fn collect_reserves(&mut self, mint: &Pubkey, interest_paid: u64) -> Result<u64> {
+        let share = (interest_paid as u128 * self.fee_config.reserve_factor_bps as u128 / 10_000) as u64;
+
+        let (reserves_owed, reserves_collected) = if *mint == self.mint_a {
+            (&mut self.reserves_owed_a, &mut self.reserves_collected_a)
+        } else {
+            (&mut self.reserves_owed_b, &mut self.reserves_collected_b)
+        };
+
+        let reserves = share.min(*reserves_owed);
+        *reserves_owed -= reserves;
+        *reserves_collected = reserves_collected
+            .checked_add(reserves)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(reserves)
+    }
+
+    /// Loads the prices of mint_a and mint_b, in that order, enforcing the pool's oracle guards.
+    pub fn load_prices(&self, oracle_a: &AccountInfo, oracle_b: &AccountInfo, clock: &Clock) -> Result<(OraclePrice, OraclePrice)> {
+        let price_a = OraclePrice::load_checked(oracle_a, self.decimals_a, &self.oracle_config, clock)?;
+        let price_b = OraclePrice::load_checked(oracle_b, self.decimals_b, &self.oracle_config, clock)?;
+
+        Ok((price_a, price_b))
+    }
+
+    /// Loads the prices of a loan's borrowed and collateral mints, in that order, enforcing the pool's oracle guards.
+    pub fn loan_prices(&self, borrow_mint: &Pubkey, oracle_a: &AccountInfo, oracle_b: &AccountInfo, clock: &Clock) -> Result<(OraclePrice, OraclePrice)> {
+        let (price_a, price_b) = self.load_prices(oracle_a, oracle_b, clock)?;
+
+        if *borrow_mint == self.mint_a {
+            Ok((price_a, price_b))
+        } else {
+            Ok((price_b, price_a))
+        }
+    }
+
+    /// Share of one side's liquidity that is currently lent out, in basis points.
+    pub fn utilization(&self, mint: &Pubkey) -> u64 {
+        if *mint == self.mint_a {
+            utilization_bps(self.total_borrowed_a, self.available_a)
+        } else {
+            utilization_bps(self.total_borrowed_b, self.available_b)
+        }
+    }
+
+    /// Current annualized borrow rate of one side.
+    pub fn borrow_rate_bps(&self, mint: &Pubkey) -> u64 {
+        self.interest_rate_model.borrow_rate_bps(self.utilization(mint))
+    }
+
+    pub fn available(&self, mint: &Pubkey) -> u64 {
+        if *mint == self.mint_a {
+            self.available_a
+        } else {
+            self.available_b
+        }
+    }
+
+    pub fn add_available(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let available = if *mint == self.mint_a {
+            &mut self.available_a
+        } else {
+            &mut self.available_b
+        };
+
+        *available = available.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Fails unless at least `amount` of a side is available to lend or withdraw.
+    pub fn require_available(&self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let available = self.available(mint);
+        if amount > available {
+            msg!("Requested {} but only {} is available in the pool", amount, available);
+            return Err(ErrorCode::InsufficientLiquidity.into());
+        }
+
+        Ok(())
+    }
+
+    /// Takes `amount` out of a side's available balance before it leaves the vault, failing if not enough is available.
+    pub fn sub_available(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        self.require_available(mint, amount)?;
+
+        if *mint == self.mint_a {
+            self.available_a -= amount;
+        } else {
+            self.available_b -= amount;
+        }
+
+        Ok(())
+    }
+
+    /// Adds interest charged outside the borrow index to the side's debt, setting aside the reserve factor's share.
+    pub fn add_interest(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        self.add_borrowed(mint, amount)?;
+
+        let reserves = (amount as u128 * self.fee_config.reserve_factor_bps as u128 / 10_000) as u64;
+        let reserves_owed = if *mint == self.mint_a {
+            &mut self.reserves_owed_a
+        } else {
+            &mut self.reserves_owed_b
+        };
+
+        *reserves_owed = reserves_owed
+            .checked_add(reserves)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn has_flash_loan(&self) -> bool {
+        self.flash_borrowed_a > 0 || self.flash_borrowed_b > 0
+    }
+
+    pub fn borrow_index(&self, mint: &Pubkey) -> u128 {
+        if *mint == self.mint_a {
+            self.borrow_index_a
+        } else {
+            self.borrow_index_b
+        }
+    }
+
+    pub fn add_borrowed(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        if *mint == self.mint_a {
+            self.total_borrowed_a = self.total_borrowed_a.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.total_borrowed_b = self.total_borrowed_b.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        self.total_borrowed = self.total_borrowed_a
+            .checked_add(self.total_borrowed_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Saturates at zero, since per-loan and pool-wide interest are rounded independently.
+    pub fn sub_borrowed(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        if *mint == self.mint_a {
+            self.total_borrowed_a = self.total_borrowed_a.saturating_sub(amount);
+        } else {
+            self.total_borrowed_b = self.total_borrowed_b.saturating_sub(amount);
+        }
+
+        self.total_borrowed = self.total_borrowed_a
+            .checked_add(self.total_borrowed_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct InterestRateModel {
+    pub base_rate_bps: u16, //Annualized rate charged at zero utilization
+    pub slope1_bps: u16, //Rate added as utilization climbs from zero to optimal
+    pub slope2_bps: u16, //Rate added as utilization climbs from optimal to 100%
+    pub optimal_utilization_bps: u16, //Kink of the curve, 1 - 10000
+}
+
+impl InterestRateModel {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.optimal_utilization_bps > 0 && self.optimal_utilization_bps <= 10_000,
+            ErrorCode::InvalidInterestRate
+        );
+
+        Ok(())
+    }
+
+    /// Annualized borrow rate in basis points for the given utilization.
+    pub fn borrow_rate_bps(&self, utilization_bps: u64) -> u64 {
+        let base = self.base_rate_bps as u64;
+        let slope1 = self.slope1_bps as u64;
+        let slope2 = self.slope2_bps as u64;
+        let optimal = self.optimal_utilization_bps as u64;
+
+        if utilization_bps <= optimal {
+            base + slope1 * utilization_bps / optimal
+        } else {
+            base + slope1 + slope2 * (utilization_bps - optimal) / (10_000 - optimal)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct LoanTerms {
+    pub grace_period: u32, //Seconds after a loan expires before it can be liquidated for expiry
+    pub min_interest_period: u32, //Seconds of interest a loan term is charged at minimum, even when repaid earlier
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct FeeConfig {
+    pub flash_loan_fee_bps: u16, //Fee on flash loans, paid into the fee vault of the borrowed side
+    pub reserve_factor_bps: u16, //Share of accrued interest kept as protocol reserves in the fee vaults
+    pub origination_fee_bps: u16, //Fee on new loans, withheld from the disbursed amount into the fee vault of the borrowed side
+    pub rollover_fee_bps: u16, //Fee on extending a loan's term, paid into the fee vault of the borrowed side
+}
+
+impl FeeConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.flash_loan_fee_bps <= 10_000
+                && self.reserve_factor_bps <= 10_000
+                && self.origination_fee_bps <= 10_000
+                && self.rollover_fee_bps <= 10_000,
+            ErrorCode::InvalidFee
+        );
+
+        Ok(())
+    }
+
+    /// Origination fee on a loan of `amount`, rounded up so that small loans cannot dodge it.
+    pub fn origination_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.origination_fee_bps as u128).div_ceil(10_000) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct LiquidationBonus {
+    pub min_bonus_bps: u16, //Bonus offered as soon as a position becomes liquidatable
+    pub max_bonus_bps: u16, //Cap the bonus ramps up to
+    pub ramp_duration: u32, //Seconds taken to ramp from the min to the max bonus
+}
+
+impl LiquidationBonus {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_bonus_bps <= self.max_bonus_bps && self.max_bonus_bps <= 10_000,
+            ErrorCode::InvalidPenalty
+        );
+
+        Ok(())
+    }
+
+    /// Bonus in basis points for an auction that started `elapsed` seconds ago, growing linearly from min to max.
+    pub fn bonus_bps(&self, elapsed: i64) -> u64 {
+        let min = self.min_bonus_bps as u64;
+        let max = self.max_bonus_bps as u64;
+        let elapsed = elapsed.max(0) as u64;
+
+        if self.ramp_duration == 0 || elapsed >= self.ramp_duration as u64 {
+            max
+        } else {
+            min + (max - min) * elapsed / self.ramp_duration as u64
+        }
+    }
+}
+
+/// Share of a side's liquidity that is currently lent out, in basis points.
+pub fn utilization_bps(borrowed: u64, available: u64) -> u64 {
+    let total = borrowed as u128 + available as u128;
+    if total == 0 {
+        return 0;
+    }
+
+    (borrowed as u128 * 10_000 / total) as u64
+}
+
+/// Caps one side of a withdrawal at what is available and adds the value of the rest, at oracle prices,
+/// to the other side. Returns the new amounts of the short side and the other side, in that order.
+pub fn cover_shortfall(amount: u64, available: u64, price: &OraclePrice, other_amount: u64, other_price: &OraclePrice) -> Result<(u64, u64)> {
+    if amount <= available {
+        return Ok((amount, other_amount));
+    }
+
+    let shortfall_value = price.value_of(amount - available)?;
+    let other_amount = other_amount
+        .checked_add(other_price.amount_for(shortfall_value)?)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok((available, other_amount))
+}
+
+fn accrue_side(borrow_index: u128, total_borrowed: u64, rate_bps: u64, elapsed: u64) -> Result<(u128, u64)> {
+    let index_delta = borrow_index
+        .checked_mul(rate_bps as u128 * elapsed as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / (10_000 * SECONDS_PER_YEAR as u128);
+
+    let new_index = borrow_index
+        .checked_add(index_delta)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let new_total_borrowed = (total_borrowed as u128)
+        .checked_mul(new_index)
+        .ok_or(ErrorCode::MathOverflow)?
+        / borrow_index.max(1);
+
+    Ok((new_index, u64::try_from(new_total_borrowed).map_err(|_| ErrorCode::MathOverflow)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::INDEX_SCALE;
+
+    const MODEL: InterestRateModel = InterestRateModel {
+        base_rate_bps: 200,
+        slope1_bps: 400,
+        slope2_bps: 6_000,
+        optimal_utilization_bps: 8_000,
+    };
+
+    const BONUS: LiquidationBonus = LiquidationBonus {
+        min_bonus_bps: 100,
+        max_bonus_bps: 500,
+        ramp_duration: 3_600,
+    };
+
+    #[test]
+    fn bonus_at_auction_start() {
+        assert_eq!(BONUS.bonus_bps(0), 100);
+
+        // Clock skew before the recorded start still pays the minimum
+        assert_eq!(BONUS.bonus_bps(-10), 100);
+    }
+
+    #[test]
+    fn bonus_mid_ramp() {
+        assert_eq!(BONUS.bonus_bps(900), 200);
+        assert_eq!(BONUS.bonus_bps(1_800), 300);
+    }
+
+    #[test]
+    fn bonus_at_cap() {
+        assert_eq!(BONUS.bonus_bps(3_600), 500);
+        assert_eq!(BONUS.bonus_bps(86_400), 500);
+
+        let instant = LiquidationBonus { ramp_duration: 0, ..BONUS };
+        assert_eq!(instant.bonus_bps(0), 500);
+    }
+
+    #[test]
+    fn borrow_rate_at_kink() {
+        assert_eq!(MODEL.borrow_rate_bps(0), 200);
+        assert_eq!(MODEL.borrow_rate_bps(4_000), 400);
+        assert_eq!(MODEL.borrow_rate_bps(8_000), 600);
+        assert_eq!(MODEL.borrow_rate_bps(9_000), 3_600);
+    }
+
+    #[test]
+    fn borrow_rate_at_full_utilization() {
+        assert_eq!(MODEL.borrow_rate_bps(10_000), 6_600);
+
+        // With the kink at 100% the steep slope is never reached
+        let model = InterestRateModel { optimal_utilization_bps: 10_000, ..MODEL };
+        assert_eq!(model.borrow_rate_bps(10_000), 600);
+    }
+
+    #[test]
+    fn utilization_of_empty_side_is_zero() {
+        assert_eq!(utilization_bps(0, 0), 0);
+        assert_eq!(utilization_bps(0, 100), 0);
+        assert_eq!(utilization_bps(100, 0), 10_000);
+        assert_eq!(utilization_bps(25, 75), 2_500);
+    }
+
+    #[test]
+    fn one_year_of_accrual() {
+        let (index, total_borrowed) = accrue_side(INDEX_SCALE, 1_000_000, 1_000, SECONDS_PER_YEAR).unwrap();
+
+        assert_eq!(index, INDEX_SCALE * 11 / 10);
+        assert_eq!(total_borrowed, 1_100_000);
+
+        // A second year compounds on the grown index
+        let (index, total_borrowed) = accrue_side(index, total_borrowed, 1_000, SECONDS_PER_YEAR).unwrap();
+
+        assert_eq!(index, INDEX_SCALE * 121 / 100);
+        assert_eq!(total_borrowed, 1_210_000);
+    }
+
+    fn price(price: i64, expo: i32, mint_decimals: u8) -> OraclePrice {
+        OraclePrice {
+            price,
+            conf: 0,
+            expo,
+            ema_price: price,
+            ema_conf: 0,
+            publish_time: 0,
+            publish_slot: 0,
+            mint_decimals,
+        }
+    }
+
+    #[test]
+    fn withdrawal_from_a_borrowed_out_side() {
+        // $2.00 per A with 6 decimals, $0.50 per B with 9 decimals
+        let price_a = price(200_000_000, -8, 6);
+        let price_b = price(50_000_000, -8, 9);
+
+        // Nothing of A is available, so its whole share of 3 A ($6) is paid as 12 B
+        let (amount_a, amount_b) = cover_shortfall(3_000_000, 0, &price_a, 1_000_000_000, &price_b).unwrap();
+        assert_eq!(amount_a, 0);
+        assert_eq!(amount_b, 13_000_000_000);
+
+        // Only the part of the share that is not available is swapped
+        let (amount_a, amount_b) = cover_shortfall(3_000_000, 1_000_000, &price_a, 1_000_000_000, &price_b).unwrap();
+        assert_eq!(amount_a, 1_000_000);
+        assert_eq!(amount_b, 9_000_000_000);
+
+        // A side with enough available is paid as is
+        let (amount_a, amount_b) = cover_shortfall(3_000_000, 3_000_000, &price_a, 1_000_000_000, &price_b).unwrap();
+        assert_eq!(amount_a, 3_000_000);
+        assert_eq!(amount_b, 1_000_000_000);
+    }
+}
@@ -7,7 +7,7 @@ pub struct LiquidityProviderInfo {
     pub liquidity_pool: Pubkey,
     pub provided_token_a: u64,
     pub provided_token_b: u64,
-    pub total_liquidity_provided: u64, 
+    pub total_liquidity_provided: u64, //USD value of the deposits when made, in VALUE_DECIMALS fixed point
     pub total_lp_tokens: u64
 }
 
@@ -1,8 +1,9 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { DecentralizedLendingPlatoform } from "../target/types/decentralized_lending_platoform";
-import { PublicKey, SystemProgram } from "@solana/web3.js";
-import {createMint, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
+import {createMint, getAccount, getOrCreateAssociatedTokenAccount, mintTo, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import { assert } from "chai";
 import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
 
 const provider = anchor.AnchorProvider.env();
@@ -18,16 +19,34 @@ let tokenVaultA: PublicKey;
 let tokenVaultB: PublicKey;
 let feeVaultA: PublicKey;
 let feeVaultB: PublicKey;
+let collateralVaultA: PublicKey;
+let collateralVaultB: PublicKey;
+
+// Pyth-format mock price accounts loaded from tests/fixtures (see Anchor.toml)
+const oracleA = new PublicKey("BbzhKxAfW3qGjguDfj1QHt4SHcNqSf9tZfctzde4N49U");
+const oracleB = new PublicKey("9aofLM4wafPrLFJzhtrk5ewE4TugGQP4WAvKJerfVExG");
 
 let liquidityProviderAccount: PublicKey;
 let providerTokenAata: PublicKey;
 let providerTokenBata: PublicKey;
 let providerLpTokenAta: PublicKey;
 
+let loanId = new anchor.BN(0);
 let borrowerAccountInfo: PublicKey;
 let borrowerAta: PublicKey;
 let borrowerCollateralAta: PublicKey;
 
+// Second loan, pushed underwater and partially liquidated by a separate liquidator
+let liquidatedLoanId = new anchor.BN(1);
+let liquidatedLoanInfo: PublicKey;
+let liquidator = Keypair.generate();
+let liquidatorAta: PublicKey;
+let liquidatorCollateralAta: PublicKey;
+
+// Third loan, closed early to pay the minimum interest period
+let earlyLoanId = new anchor.BN(2);
+let earlyLoanInfo: PublicKey;
+
 before(async () => {
   tokenMintA = await createMint(
     provider.connection,
@@ -75,8 +94,18 @@ before(async () => {
     program.programId
   );
 
+  [collateralVaultA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("collateral_vault_a"), tokenMintA.toBuffer(), liquidityPool.toBuffer()],
+    program.programId
+  );
+
+  [collateralVaultB] = PublicKey.findProgramAddressSync(
+    [Buffer.from("collateral_vault_b"), tokenMintB.toBuffer(), liquidityPool.toBuffer()],
+    program.programId
+  );
+
   [liquidityProviderAccount] = PublicKey.findProgramAddressSync(
-    [Buffer.from("liquidity_provider"), provider.wallet.publicKey.toBuffer()],
+    [Buffer.from("liquidity_provider"), liquidityPool.toBuffer(), provider.wallet.publicKey.toBuffer()],
     program.programId
   );
 
@@ -99,7 +128,7 @@ before(async () => {
   providerTokenBata = TokenBata.address;
 
   [borrowerAccountInfo] = PublicKey.findProgramAddressSync(
-    [Buffer.from("borrower_account"), provider.wallet.publicKey.toBuffer()],
+    [Buffer.from("borrower_account"), liquidityPool.toBuffer(), provider.wallet.publicKey.toBuffer(), loanId.toArrayLike(Buffer, "le", 8)],
     program.programId
   );
 
@@ -120,6 +149,16 @@ before(async () => {
   );
 
   borrowerCollateralAta = borrowerCollateralAccount.address;
+
+  [liquidatedLoanInfo] = PublicKey.findProgramAddressSync(
+    [Buffer.from("borrower_account"), liquidityPool.toBuffer(), provider.wallet.publicKey.toBuffer(), liquidatedLoanId.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+
+  [earlyLoanInfo] = PublicKey.findProgramAddressSync(
+    [Buffer.from("borrower_account"), liquidityPool.toBuffer(), provider.wallet.publicKey.toBuffer(), earlyLoanId.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
 })
 
 describe("decentralized_lending_platoform", () => {
@@ -133,10 +172,15 @@ describe("decentralized_lending_platoform", () => {
   it("Initialize liquidity pool", async () => {
     let ltvRatio = 80;
     let liquidationThreshold = 85;
-    let liquidationPenalty = 5;
-    let interestRate = 3;
-
-    const tx = await program.methods.initializeLiquidityPool(ltvRatio, liquidationThreshold, liquidationPenalty, interestRate).accountsPartial({
+    let liquidationBonus = { minBonusBps: 100, maxBonusBps: 500, rampDuration: 3600 };
+    let interestRateModel = {
+      baseRateBps: 200,
+      slope1Bps: 400,
+      slope2Bps: 6000,
+      optimalUtilizationBps: 8000,
+    };
+
+    const tx = await program.methods.initializeLiquidityPool(ltvRatio, liquidationThreshold, liquidationBonus, interestRateModel).accountsPartial({
       creator: provider.wallet.publicKey,
       tokenMintA,
       tokenMintB,
@@ -146,6 +190,10 @@ describe("decentralized_lending_platoform", () => {
       tokenVaultB,
       feeVaultA,
       feeVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      oracleA,
+      oracleB,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
     }).signers([provider.wallet.payer]).rpc();
@@ -166,10 +214,55 @@ describe("decentralized_lending_platoform", () => {
   it("Update pool parameters", async () => {
     let newLtvRatio = 75;
     let newLiquidationThreshold = 80;
-    let newLiquidationPenalty = 3;
-    let newInterestRate = 2;
+    let newLiquidationBonus = { minBonusBps: 50, maxBonusBps: 300, rampDuration: 1800 };
+    let newInterestRateModel = {
+      baseRateBps: 100,
+      slope1Bps: 400,
+      slope2Bps: 7500,
+      optimalUtilizationBps: 8000,
+    };
+    let newCloseFactor = 50;
+    let newLoanTerms = {
+      gracePeriod: 24 * 60 * 60,
+      minInterestPeriod: 24 * 60 * 60,
+    };
+
+    const tx = await program.methods.updatePoolParameters(newLtvRatio, newLiquidationThreshold, newLiquidationBonus, newInterestRateModel, newCloseFactor, newLoanTerms).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool
+    }).signers([provider.wallet.payer]).rpc();
 
-    const tx = await program.methods.updatePoolParameters(newLtvRatio, newLiquidationThreshold, newLiquidationPenalty, newInterestRate).accountsPartial({
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Update pool oracles", async () => {
+    // The fixture prices never update, so only keep the confidence and EMA deviation guards
+    let oracleConfig = {
+      maxPriceAgeSlots: new anchor.BN(0),
+      maxPriceAgeSeconds: 0,
+      maxConfidenceBps: 200,
+      maxEmaDeviationBps: 500,
+    };
+
+    const tx = await program.methods.updatePoolOracles(oracleConfig).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool,
+      oracleA,
+      oracleB
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Update pool fees", async () => {
+    let feeConfig = {
+      flashLoanFeeBps: 9,
+      reserveFactorBps: 1000,
+      originationFeeBps: 50,
+      rolloverFeeBps: 25,
+    };
+
+    const tx = await program.methods.updatePoolFees(feeConfig).accountsPartial({
       creator: provider.wallet.publicKey,
       liquidityPool
     }).signers([provider.wallet.payer]).rpc();
@@ -177,9 +270,24 @@ describe("decentralized_lending_platoform", () => {
     console.log(`Transaction Signature: ${tx}`);
   });
 
+  it("Update pool treasury", async () => {
+    let maxFeeWithdrawalA = new anchor.BN(1_000);
+    let maxFeeWithdrawalB = new anchor.BN(1_000);
+
+    const tx = await program.methods.updatePoolTreasury(maxFeeWithdrawalA, maxFeeWithdrawalB).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool,
+      treasuryA: providerTokenAata,
+      treasuryB: providerTokenBata
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
   it("Initialize liquidity provider", async () => {
     const tx = await program.methods.initializeLiquidityProvider().accountsPartial({  
       provider: provider.wallet.publicKey,
+      liquidityPool,
       liquidityProviderAccount,
       systemProgram: SystemProgram.programId
     }).signers([provider.wallet.payer]).rpc();
@@ -221,6 +329,33 @@ describe("decentralized_lending_platoform", () => {
       tokenVaultA,
       tokenVaultB,
       providerLpTokenAta,
+      oracleA,
+      oracleB,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Provide single-sided liquidity", async () => {
+    let tokenAamount = new anchor.BN(30);
+    let tokenBamount = new anchor.BN(0);
+    const tx = await program.methods.provideLiquidity(tokenAamount, tokenBamount).accounts({
+      provider: provider.wallet.publicKey,
+      tokenMintA,
+      tokenMintB,
+      lpTokenMint,
+      liquidityPool,
+      liquidityProviderAccount,
+      providerTokenAata,
+      providerTokenBata,
+      tokenVaultA,
+      tokenVaultB,
+      providerLpMintAta: providerLpTokenAta,
+      oracleA,
+      oracleB,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_PROGRAM_ID
@@ -232,7 +367,7 @@ describe("decentralized_lending_platoform", () => {
   it("Borrow funds", async () => {
     const borrowAmount = new anchor.BN(15);
     const borrowDuration = 0; 
-    const tx = await program.methods.borrowFunds(borrowAmount, borrowDuration).accounts({
+    const tx = await program.methods.borrowFunds(loanId, borrowAmount, borrowDuration).accounts({
       borrower: provider.wallet.publicKey,
       wantedMint: tokenMintA,
       givingMint: tokenMintB,
@@ -242,8 +377,12 @@ describe("decentralized_lending_platoform", () => {
       tokenVaultB,
       feeVaultA,  
       feeVaultB,
+      collateralVaultA,
+      collateralVaultB,
       borrowerAta,
       borrowerCollateralAta,
+      oracleA,
+      oracleB,
       systemProgram: SystemProgram.programId,
       tokenProgram: TOKEN_PROGRAM_ID,
       associatedTokenProgram: ASSOCIATED_PROGRAM_ID
@@ -252,4 +391,447 @@ describe("decentralized_lending_platoform", () => {
     console.log(`Transaction Signture: ${tx}`);
   })
 
-});
\ No newline at end of file
+  it("Add collateral", async () => {
+    const collateralAmount = new anchor.BN(5);
+    const tx = await program.methods.addCollateral(collateralAmount).accounts({
+      borrower: provider.wallet.publicKey,
+      collateralMint: tokenMintB,
+      borrowerAccountInfo,
+      liquidityPool,
+      collateralVaultA,
+      collateralVaultB,
+      borrowerCollateralAta,
+      oracleA,
+      oracleB,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Borrow more", async () => {
+    const borrowAmount = new anchor.BN(1);
+    const tx = await program.methods.borrowMore(borrowAmount).accounts({
+      borrower: provider.wallet.publicKey,
+      loanMint: tokenMintA,
+      borrowerAccountInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerAta,
+      oracleA,
+      oracleB,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Withdraw collateral", async () => {
+    const collateralAmount = new anchor.BN(1);
+    const tx = await program.methods.withdrawCollateral(collateralAmount).accounts({
+      borrower: provider.wallet.publicKey,
+      collateralMint: tokenMintB,
+      borrowerAccountInfo,
+      liquidityPool,
+      collateralVaultA,
+      collateralVaultB,
+      borrowerCollateralAta,
+      oracleA,
+      oracleB,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Extend loan", async () => {
+    const borrowDuration = 2;
+    const tx = await program.methods.extendLoan(borrowDuration).accounts({
+      borrower: provider.wallet.publicKey,
+      loanMint: tokenMintA,
+      borrowerAccountInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Withdraw liquidity", async () => {
+    let lpTokenAmount = new anchor.BN(50);
+    const tx = await program.methods.withdrawLiquidity(lpTokenAmount).accounts({
+      provider: provider.wallet.publicKey,
+      tokenMintA,
+      tokenMintB,
+      lpTokenMint,
+      liquidityPool,
+      liquidityProviderAccount,
+      providerTokenAata,
+      providerTokenBata,
+      tokenVaultA,
+      tokenVaultB,
+      providerLpMintAta: providerLpTokenAta,
+      oracleA,
+      oracleB,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Flash borrow and repay", async () => {
+    const flashAmount = new anchor.BN(10);
+
+    const flashBorrowIx = await program.methods.flashBorrow(flashAmount).accountsPartial({
+      borrower: provider.wallet.publicKey,
+      tokenMint: tokenMintA,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      borrowerAta,
+      instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    }).instruction();
+
+    const flashRepayIx = await program.methods.flashRepay(flashAmount).accountsPartial({
+      borrower: provider.wallet.publicKey,
+      tokenMint: tokenMintA,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerAta,
+    }).instruction();
+
+    const tx = await provider.sendAndConfirm(new anchor.web3.Transaction().add(flashBorrowIx, flashRepayIx));
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Flash borrow without a repay fails", async () => {
+    const flashBorrowIx = await program.methods.flashBorrow(new anchor.BN(10)).accountsPartial({
+      borrower: provider.wallet.publicKey,
+      tokenMint: tokenMintA,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      borrowerAta,
+      instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    }).instruction();
+
+    try {
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(flashBorrowIx));
+      assert.fail("Flash borrow without a matching repay should fail");
+    } catch (err) {
+      assert.include(err.toString() + (err.logs ?? []).join("\n"), "FlashRepayNotFound");
+    }
+  });
+
+  it("Withdraw protocol fees", async () => {
+    // The flash loan above paid its fee into fee_vault_a
+    let amountA = new anchor.BN(1);
+    let amountB = new anchor.BN(0);
+
+    const tx = await program.methods.withdrawProtocolFees(amountA, amountB).accountsPartial({
+      authority: provider.wallet.publicKey,
+      tokenMintA,
+      tokenMintB,
+      liquidityPool,
+      feeVaultA,
+      feeVaultB,
+      treasuryA: providerTokenAata,
+      treasuryB: providerTokenBata,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+  });
+
+  it("Flag unhealthy loan", async () => {
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      tokenMintB,
+      borrowerCollateralAta,
+      provider.wallet.publicKey,
+      100
+    );
+
+    // Both mints are priced at $1, so 100 collateral at a 75% LTV borrows 75
+    const openTx = await program.methods.borrowFunds(liquidatedLoanId, new anchor.BN(100), 0).accounts({
+      borrower: provider.wallet.publicKey,
+      wantedMint: tokenMintA,
+      givingMint: tokenMintB,
+      borrowerAccountInfo: liquidatedLoanInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      feeVaultA,
+      feeVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      borrowerAta,
+      borrowerCollateralAta,
+      oracleA,
+      oracleB,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${openTx}`);
+
+    // Lowering the liquidation threshold to 50% puts the loan underwater; a flat 5% bonus keeps the seized amount predictable
+    const paramsTx = await program.methods.updatePoolParameters(
+      40,
+      50,
+      { minBonusBps: 500, maxBonusBps: 500, rampDuration: 0 },
+      { baseRateBps: 100, slope1Bps: 400, slope2Bps: 7500, optimalUtilizationBps: 8000 },
+      50,
+      { gracePeriod: 24 * 60 * 60, minInterestPeriod: 24 * 60 * 60 }
+    ).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${paramsTx}`);
+
+    const tx = await program.methods.flagUnhealthyLoan().accountsPartial({
+      borrower: provider.wallet.publicKey,
+      borrowerAccountInfo: liquidatedLoanInfo,
+      liquidityPool,
+      oracleA,
+      oracleB
+    }).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+
+    const loan = await program.account.borrowInfo.fetch(liquidatedLoanInfo);
+    assert.isTrue(loan.unhealthySince.gtn(0));
+  });
+
+  it("Liquidate", async () => {
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(liquidator.publicKey, LAMPORTS_PER_SOL),
+      "confirmed"
+    );
+
+    liquidatorAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      tokenMintA,
+      liquidator.publicKey
+    )).address;
+
+    liquidatorCollateralAta = (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      tokenMintB,
+      liquidator.publicKey
+    )).address;
+
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      tokenMintA,
+      liquidatorAta,
+      provider.wallet.publicKey,
+      100
+    );
+
+    const before = await program.account.borrowInfo.fetch(liquidatedLoanInfo);
+    const debt = before.totalBorrowed.add(before.accruedInterest).toNumber();
+    const borrowerCollateralBefore = (await getAccount(provider.connection, borrowerCollateralAta)).amount;
+
+    // Asking to repay everything is capped at the 50% close factor
+    const tx = await program.methods.liquidate(new anchor.BN(100)).accountsPartial({
+      liquidator: liquidator.publicKey,
+      borrower: provider.wallet.publicKey,
+      loanMint: tokenMintA,
+      collateralMint: tokenMintB,
+      borrowerAccountInfo: liquidatedLoanInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerCollateralAta,
+      liquidatorAta,
+      liquidatorCollateralAta,
+      oracleA,
+      oracleB,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    }).signers([liquidator]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+
+    const repaid = Math.floor(debt * 50 / 100);
+    const seized = Math.floor(repaid * 10_500 / 10_000);
+
+    const after = await program.account.borrowInfo.fetch(liquidatedLoanInfo);
+    assert.equal((await getAccount(provider.connection, liquidatorAta)).amount, BigInt(100 - repaid));
+    assert.equal((await getAccount(provider.connection, liquidatorCollateralAta)).amount, BigInt(seized));
+    assert.equal(after.totalCollateral.toNumber(), before.totalCollateral.toNumber() - seized);
+    assert.isFalse(after.isClosed);
+
+    // A partial liquidation leaves the rest of the collateral on the loan rather than returning it
+    assert.equal((await getAccount(provider.connection, borrowerCollateralAta)).amount, borrowerCollateralBefore);
+  });
+
+  it("Repay funds", async () => {
+    const before = await program.account.borrowInfo.fetch(liquidatedLoanInfo);
+    const borrowerCollateralBefore = (await getAccount(provider.connection, borrowerCollateralAta)).amount;
+
+    // The fixture prices are far older than a second, so every price-dependent instruction is now blocked,
+    // but a borrower can still repay
+    const staleOracleConfig = {
+      maxPriceAgeSlots: new anchor.BN(0),
+      maxPriceAgeSeconds: 1,
+      maxConfidenceBps: 200,
+      maxEmaDeviationBps: 500,
+    };
+
+    await program.methods.updatePoolOracles(staleOracleConfig).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool,
+      oracleA,
+      oracleB
+    }).signers([provider.wallet.payer]).rpc();
+
+    // u64::MAX repays whatever the debt has accrued to by the time the transaction lands
+    const tx = await program.methods.repayFunds(new anchor.BN("18446744073709551615")).accountsPartial({
+      borrower: provider.wallet.publicKey,
+      wantedMint: tokenMintA,
+      givingMint: tokenMintB,
+      borrowerAccountInfo: liquidatedLoanInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerAta,
+      borrowerCollateralAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+
+    // Closing out the debt returns whatever collateral the liquidation left behind
+    const after = await program.account.borrowInfo.fetch(liquidatedLoanInfo);
+    assert.isTrue(after.isClosed);
+    assert.equal(after.totalCollateral.toNumber(), 0);
+    assert.equal(
+      (await getAccount(provider.connection, borrowerCollateralAta)).amount,
+      borrowerCollateralBefore + BigInt(before.totalCollateral.toNumber())
+    );
+
+    await program.methods.updatePoolOracles({ ...staleOracleConfig, maxPriceAgeSeconds: 0 }).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool,
+      oracleA,
+      oracleB
+    }).signers([provider.wallet.payer]).rpc();
+  });
+
+  it("Repay funds inside the minimum interest period", async () => {
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      tokenMintB,
+      borrowerCollateralAta,
+      provider.wallet.publicKey,
+      300
+    );
+
+    const openTx = await program.methods.borrowFunds(earlyLoanId, new anchor.BN(300), 0).accounts({
+      borrower: provider.wallet.publicKey,
+      wantedMint: tokenMintA,
+      givingMint: tokenMintB,
+      borrowerAccountInfo: earlyLoanInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      feeVaultA,
+      feeVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      borrowerAta,
+      borrowerCollateralAta,
+      oracleA,
+      oracleB,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${openTx}`);
+
+    // A year-long minimum period makes the top-up large enough to see on a small loan
+    const paramsTx = await program.methods.updatePoolParameters(
+      40,
+      50,
+      { minBonusBps: 500, maxBonusBps: 500, rampDuration: 0 },
+      { baseRateBps: 100, slope1Bps: 400, slope2Bps: 7500, optimalUtilizationBps: 8000 },
+      50,
+      { gracePeriod: 24 * 60 * 60, minInterestPeriod: 365 * 24 * 60 * 60 }
+    ).accountsPartial({
+      creator: provider.wallet.publicKey,
+      liquidityPool
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${paramsTx}`);
+
+    const before = await program.account.borrowInfo.fetch(earlyLoanInfo);
+    const debt = before.totalBorrowed.add(before.accruedInterest).toNumber();
+    const balanceBefore = (await getAccount(provider.connection, borrowerAta)).amount;
+
+    const maxRepay = debt * 2;
+    const tx = await program.methods.repayFunds(new anchor.BN(maxRepay)).accountsPartial({
+      borrower: provider.wallet.publicKey,
+      wantedMint: tokenMintA,
+      givingMint: tokenMintB,
+      borrowerAccountInfo: earlyLoanInfo,
+      liquidityPool,
+      tokenVaultA,
+      tokenVaultB,
+      collateralVaultA,
+      collateralVaultB,
+      feeVaultA,
+      feeVaultB,
+      borrowerAta,
+      borrowerCollateralAta,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_PROGRAM_ID
+    }).signers([provider.wallet.payer]).rpc();
+
+    console.log(`Transaction Signature: ${tx}`);
+
+    // The closing amount includes the top-up, and never exceeds what the borrower signed for
+    const transferred = Number(balanceBefore - (await getAccount(provider.connection, borrowerAta)).amount);
+    const after = await program.account.borrowInfo.fetch(earlyLoanInfo);
+    assert.isTrue(after.isClosed);
+    assert.isAbove(transferred, debt);
+    assert.isAtMost(transferred, maxRepay);
+    assert.equal(after.repaidAmount.toNumber(), transferred);
+  });
+
+});